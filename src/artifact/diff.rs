@@ -0,0 +1,227 @@
+use std::fmt;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::artifact::internal_artifact::{ArtifactStatName, InternalArtifact, StatValue};
+
+pub struct SubStatChange {
+    pub name: ArtifactStatName,
+    // None if the sub stat is newly added by the upgrade
+    pub old_value: Option<StatValue>,
+    pub new_value: StatValue,
+}
+
+pub struct ArtifactUpgrade {
+    pub old: InternalArtifact,
+    pub new: InternalArtifact,
+    pub changes: Vec<SubStatChange>,
+}
+
+pub struct ArtifactDiff {
+    pub added: Vec<InternalArtifact>,
+    pub removed: Vec<InternalArtifact>,
+    pub upgraded: Vec<ArtifactUpgrade>,
+    pub unchanged: usize,
+}
+
+// Equip is ignored, moving an artifact to another character does not make it a different artifact.
+// So is the main stat value, readers of formats without it fill in the table value which may differ from the game
+fn is_same(old: &InternalArtifact, new: &InternalArtifact) -> bool {
+    old.set_name == new.set_name
+        && old.slot == new.slot
+        && old.star == new.star
+        && old.level == new.level
+        && old.main_stat.name == new.main_stat.name
+        && old.sub_stats() == new.sub_stats()
+}
+
+// Whether `new` can be the result of leveling up `old`
+fn is_upgrade_of(old: &InternalArtifact, new: &InternalArtifact) -> bool {
+    if old.set_name != new.set_name
+        || old.slot != new.slot
+        || old.star != new.star
+        || old.main_stat.name != new.main_stat.name
+        || old.level >= new.level
+    {
+        return false;
+    }
+
+    let old_subs = old.sub_stats();
+    let new_subs = new.sub_stats();
+    if old_subs.len() > new_subs.len() {
+        return false;
+    }
+    for (o, n) in old_subs.iter().zip(new_subs.iter()) {
+        if o.name != n.name || o.value.tenths > n.value.tenths {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn get_changes(old: &InternalArtifact, new: &InternalArtifact) -> Vec<SubStatChange> {
+    let old_subs = old.sub_stats();
+    let mut changes: Vec<SubStatChange> = Vec::new();
+    for (i, n) in new.sub_stats().iter().enumerate() {
+        match old_subs.get(i) {
+            Some(o) => {
+                if n.value.tenths > o.value.tenths {
+                    changes.push(SubStatChange {
+                        name: n.name.clone(),
+                        old_value: Some(o.value),
+                        new_value: n.value,
+                    });
+                }
+            }
+            None => changes.push(SubStatChange {
+                name: n.name.clone(),
+                old_value: None,
+                new_value: n.value,
+            }),
+        }
+    }
+    changes
+}
+
+// For each old artifact, the index of the new artifact it became, if any.
+// Exact matches are paired first, then upgrades
+pub fn pair_artifacts(old: &[InternalArtifact], new: &[InternalArtifact]) -> Vec<Option<usize>> {
+    let mut pairs: Vec<Option<usize>> = vec![None; old.len()];
+    let mut new_matched = vec![false; new.len()];
+
+    for (i, o) in old.iter().enumerate() {
+        for (j, n) in new.iter().enumerate() {
+            if !new_matched[j] && is_same(o, n) {
+                pairs[i] = Some(j);
+                new_matched[j] = true;
+                break;
+            }
+        }
+    }
+
+    // prefer the candidate with the least levels gained
+    for (i, o) in old.iter().enumerate() {
+        if pairs[i].is_some() {
+            continue;
+        }
+
+        let mut best: Option<usize> = None;
+        for (j, n) in new.iter().enumerate() {
+            if new_matched[j] || !is_upgrade_of(o, n) {
+                continue;
+            }
+            match best {
+                Some(b) if new[b].level <= n.level => (),
+                _ => best = Some(j),
+            }
+        }
+
+        if let Some(j) = best {
+            pairs[i] = Some(j);
+            new_matched[j] = true;
+        }
+    }
+
+    pairs
+}
+
+impl ArtifactDiff {
+    pub fn new(old: &[InternalArtifact], new: &[InternalArtifact]) -> ArtifactDiff {
+        ArtifactDiff::from_pairs(old, new, &pair_artifacts(old, new))
+    }
+
+    // `pairs` as returned by `pair_artifacts`
+    pub fn from_pairs(old: &[InternalArtifact], new: &[InternalArtifact], pairs: &[Option<usize>]) -> ArtifactDiff {
+        let mut new_matched = vec![false; new.len()];
+        let mut removed: Vec<InternalArtifact> = Vec::new();
+        let mut upgraded: Vec<ArtifactUpgrade> = Vec::new();
+        let mut unchanged = 0;
+
+        for (o, pair) in old.iter().zip(pairs.iter()) {
+            match *pair {
+                Some(j) => {
+                    new_matched[j] = true;
+                    if is_same(o, &new[j]) {
+                        unchanged += 1;
+                    } else {
+                        upgraded.push(ArtifactUpgrade {
+                            old: o.clone(),
+                            new: new[j].clone(),
+                            changes: get_changes(o, &new[j]),
+                        });
+                    }
+                }
+                None => removed.push(o.clone()),
+            }
+        }
+
+        let added = new.iter().zip(new_matched.iter())
+            .filter(|(_, &m)| !m)
+            .map(|(a, _)| a.clone())
+            .collect();
+
+        ArtifactDiff {
+            added,
+            removed,
+            upgraded,
+            unchanged,
+        }
+    }
+}
+
+impl fmt::Display for ArtifactDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "new: {}, removed: {}, upgraded: {}, unchanged: {}",
+                 self.added.len(), self.removed.len(), self.upgraded.len(), self.unchanged)?;
+
+        for art in self.added.iter() {
+            writeln!(f, "+ {}", art)?;
+        }
+        for art in self.removed.iter() {
+            writeln!(f, "- {}", art)?;
+        }
+        for up in self.upgraded.iter() {
+            writeln!(f, "~ {}", up.old)?;
+            writeln!(f, "    +{} -> +{}", up.old.level, up.new.level)?;
+            for c in up.changes.iter() {
+                match c.old_value {
+                    Some(v) => writeln!(f, "    {:?}: {} -> {}", c.name, v, c.new_value)?,
+                    None => writeln!(f, "    {:?}: new {}", c.name, c.new_value)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for SubStatChange {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(Some(3))?;
+        root.serialize_entry("name", &self.name.to_mona())?;
+        root.serialize_entry("oldValue", &self.old_value.map(|v| v.as_f64()))?;
+        root.serialize_entry("newValue", &self.new_value.as_f64())?;
+        root.end()
+    }
+}
+
+impl Serialize for ArtifactUpgrade {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(Some(3))?;
+        root.serialize_entry("old", &self.old)?;
+        root.serialize_entry("new", &self.new)?;
+        root.serialize_entry("changes", &self.changes)?;
+        root.end()
+    }
+}
+
+impl Serialize for ArtifactDiff {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(Some(4))?;
+        root.serialize_entry("new", &self.added)?;
+        root.serialize_entry("removed", &self.removed)?;
+        root.serialize_entry("upgraded", &self.upgraded)?;
+        root.serialize_entry("unchanged", &self.unchanged)?;
+        root.end()
+    }
+}
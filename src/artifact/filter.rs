@@ -0,0 +1,411 @@
+use crate::artifact::internal_artifact::{ArtifactSetName, ArtifactSlot, ArtifactStatName, InternalArtifact};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn compare(&self, a: u32, b: u32) -> bool {
+        match self {
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            CompareOp::Lt => a < b,
+            CompareOp::Le => a <= b,
+            CompareOp::Gt => a > b,
+            CompareOp::Ge => a >= b,
+        }
+    }
+
+    // the smallest value `v` can have when `v op x` holds
+    fn lower_bound(&self, x: u32) -> Option<u32> {
+        match self {
+            CompareOp::Eq | CompareOp::Ge => Some(x),
+            CompareOp::Gt => Some(x + 1),
+            _ => None,
+        }
+    }
+}
+
+// A predicate parsed from an expression such as
+// "set in [EmblemOfSeveredFate, ShimenawaReminiscence] and level >= 16 and main != hp"
+#[derive(Debug, Clone)]
+pub enum ArtifactFilter {
+    And(Box<ArtifactFilter>, Box<ArtifactFilter>),
+    Or(Box<ArtifactFilter>, Box<ArtifactFilter>),
+    Not(Box<ArtifactFilter>),
+    Set(Vec<ArtifactSetName>),
+    Slot(Vec<ArtifactSlot>),
+    Main(Vec<ArtifactStatName>),
+    // any of the sub stats is one of them
+    Sub(Vec<ArtifactStatName>),
+    Star(CompareOp, u32),
+    Level(CompareOp, u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Op(CompareOp),
+    In,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let token = match c {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            },
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            ',' => Token::Comma,
+            '=' | '!' | '<' | '>' => {
+                let with_eq = chars.get(i + 1) == Some(&'=');
+                let op = match (c, with_eq) {
+                    ('=', _) => CompareOp::Eq,
+                    ('!', true) => CompareOp::Ne,
+                    ('<', true) => CompareOp::Le,
+                    ('<', false) => CompareOp::Lt,
+                    ('>', true) => CompareOp::Ge,
+                    ('>', false) => CompareOp::Gt,
+                    _ => return Err(String::from("expected != after !")),
+                };
+                if with_eq {
+                    i += 1;
+                }
+                Token::Op(op)
+            },
+            _ if c.is_ascii_alphanumeric() || c == '_' => {
+                let start = i;
+                while i + 1 < chars.len() && (chars[i + 1].is_ascii_alphanumeric() || chars[i + 1] == '_') {
+                    i += 1;
+                }
+                let name: String = chars[start..=i].iter().collect();
+                match name.to_lowercase().as_str() {
+                    "in" => Token::In,
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Name(name),
+                }
+            },
+            _ => return Err(format!("unexpected character: {}", c)),
+        };
+        tokens.push(token);
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+// yas names, GOOD keys and yas names in lower case, e.g. "CriticalDamage", "critDMG_" or "hp"
+fn parse_name<T>(s: &str, from_yas: fn(&str) -> Option<T>, from_good: fn(&str) -> Option<T>) -> Option<T> {
+    let mut chars = s.chars();
+    let capitalized: String = match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => return None,
+    };
+    from_yas(s).or_else(|| from_good(s)).or_else(|| from_yas(&capitalized))
+}
+
+fn parse_stat_name(s: &str) -> Option<ArtifactStatName> {
+    parse_name(s, ArtifactStatName::from_yas, ArtifactStatName::from_good)
+}
+
+fn parse_number(s: &str) -> Option<u32> {
+    s.parse::<u32>().ok()
+}
+
+fn parse_values<T>(field: &str, values: &[String], parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, String> {
+    values.iter().map(|v| parse(v).ok_or(format!("invalid {}: {}", field, v))).collect()
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(ref t) if *t == expected => Ok(()),
+            Some(t) => Err(format!("expected {:?}, found {:?}", expected, t)),
+            None => Err(format!("expected {:?} at the end", expected)),
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Name(s)) => Ok(s),
+            Some(t) => Err(format!("expected a name or a number, found {:?}", t)),
+            None => Err(String::from("unexpected end")),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<ArtifactFilter, String> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            let right = self.parse_and()?;
+            left = ArtifactFilter::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<ArtifactFilter, String> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            let right = self.parse_unary()?;
+            left = ArtifactFilter::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<ArtifactFilter, String> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.next();
+                Ok(ArtifactFilter::Not(Box::new(self.parse_unary()?)))
+            },
+            Some(Token::LParen) => {
+                self.next();
+                let filter = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Ok(filter)
+            },
+            _ => self.parse_condition(),
+        }
+    }
+
+    // "field op value" or "field in [value, ...]"
+    fn parse_condition(&mut self) -> Result<ArtifactFilter, String> {
+        let field = self.name()?.to_lowercase();
+        let (op, values) = match self.next() {
+            Some(Token::Op(op)) => (op, vec![self.name()?]),
+            Some(Token::In) => {
+                self.expect(Token::LBracket)?;
+                let mut values = vec![self.name()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.next();
+                    values.push(self.name()?);
+                }
+                self.expect(Token::RBracket)?;
+                (CompareOp::Eq, values)
+            },
+            Some(t) => return Err(format!("expected a comparison after {}, found {:?}", field, t)),
+            None => return Err(format!("expected a comparison after {}", field)),
+        };
+
+        let filter = match field.as_str() {
+            "star" | "level" => {
+                let is_star = field == "star";
+                // a list is a choice between its values
+                let mut filters = parse_values(&field, &values, parse_number)?
+                    .into_iter()
+                    .map(|x| if is_star { ArtifactFilter::Star(op, x) } else { ArtifactFilter::Level(op, x) });
+                let first = filters.next().unwrap();
+                return Ok(filters.fold(first, |l, r| ArtifactFilter::Or(Box::new(l), Box::new(r))));
+            },
+            "set" => ArtifactFilter::Set(parse_values(&field, &values, |v| {
+                parse_name(v, ArtifactSetName::from_yas, ArtifactSetName::from_good)
+            })?),
+            "slot" => ArtifactFilter::Slot(parse_values(&field, &values, |v| {
+                parse_name(v, ArtifactSlot::from_yas, ArtifactSlot::from_good)
+            })?),
+            "main" => ArtifactFilter::Main(parse_values(&field, &values, parse_stat_name)?),
+            "sub" => ArtifactFilter::Sub(parse_values(&field, &values, parse_stat_name)?),
+            _ => return Err(format!("unknown field: {}", field)),
+        };
+
+        match op {
+            CompareOp::Eq => Ok(filter),
+            CompareOp::Ne => Ok(ArtifactFilter::Not(Box::new(filter))),
+            _ => Err(format!("{} can only be compared with =, != or in", field)),
+        }
+    }
+}
+
+impl ArtifactFilter {
+    pub fn parse(s: &str) -> Result<ArtifactFilter, String> {
+        let tokens = tokenize(s)?;
+        if tokens.is_empty() {
+            return Err(String::from("empty filter"));
+        }
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let filter = parser.parse_or()?;
+        match parser.peek() {
+            Some(t) => Err(format!("unexpected {:?}", t)),
+            None => Ok(filter),
+        }
+    }
+
+    pub fn matches(&self, artifact: &InternalArtifact) -> bool {
+        match self {
+            ArtifactFilter::And(l, r) => l.matches(artifact) && r.matches(artifact),
+            ArtifactFilter::Or(l, r) => l.matches(artifact) || r.matches(artifact),
+            ArtifactFilter::Not(f) => !f.matches(artifact),
+            ArtifactFilter::Set(names) => names.contains(&artifact.set_name),
+            ArtifactFilter::Slot(slots) => slots.contains(&artifact.slot),
+            ArtifactFilter::Main(names) => names.contains(&artifact.main_stat.name),
+            ArtifactFilter::Sub(names) => artifact.sub_stats().iter().any(|s| names.contains(&s.name)),
+            ArtifactFilter::Star(op, x) => op.compare(artifact.star, *x),
+            ArtifactFilter::Level(op, x) => op.compare(artifact.level, *x),
+        }
+    }
+
+    fn lower_bound(&self, leaf: &dyn Fn(&ArtifactFilter) -> Option<u32>) -> Option<u32> {
+        match self {
+            ArtifactFilter::And(l, r) => match (l.lower_bound(leaf), r.lower_bound(leaf)) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (a, None) => a,
+                (None, b) => b,
+            },
+            // kept simple, a bound is only taken from conditions every match has to meet
+            ArtifactFilter::Or(_, _) => None,
+            ArtifactFilter::Not(_) => None,
+            f => leaf(f),
+        }
+    }
+
+    // The lowest star a matching artifact can have, if the filter implies one
+    pub fn min_star(&self) -> Option<u32> {
+        self.lower_bound(&|f| match f {
+            ArtifactFilter::Star(op, x) => op.lower_bound(*x),
+            _ => None,
+        })
+    }
+
+    // The lowest level a matching artifact can have, if the filter implies one
+    pub fn min_level(&self) -> Option<u32> {
+        self.lower_bound(&|f| match f {
+            ArtifactFilter::Level(op, x) => op.lower_bound(*x),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::internal_artifact::{ArtifactStat, StatValue};
+
+    fn stat(name: ArtifactStatName, value: f64) -> ArtifactStat {
+        let value = StatValue::from_display(value, name.unit());
+        ArtifactStat { name, value }
+    }
+
+    fn artifact(set_name: ArtifactSetName, star: u32, level: u32) -> InternalArtifact {
+        InternalArtifact {
+            set_name,
+            slot: ArtifactSlot::Flower,
+            star,
+            level,
+            main_stat: stat(ArtifactStatName::Hp, 717.0),
+            sub_stat_1: Some(stat(ArtifactStatName::Critical, 3.9)),
+            sub_stat_2: None,
+            sub_stat_3: None,
+            sub_stat_4: None,
+            equip: None,
+        }
+    }
+
+    fn matches(filter: &str, artifact: &InternalArtifact) -> bool {
+        ArtifactFilter::parse(filter).unwrap().matches(artifact)
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let low_level_5 = artifact(ArtifactSetName::EmblemOfSeveredFate, 5, 0);
+        assert!(matches("star == 5 or star == 4 and level == 20", &low_level_5));
+        assert!(matches("star == 4 and level == 20 or star == 5", &low_level_5));
+        assert!(!matches("star == 4 or star == 3 and level == 0", &low_level_5));
+    }
+
+    #[test]
+    fn parentheses() {
+        let low_level_5 = artifact(ArtifactSetName::EmblemOfSeveredFate, 5, 0);
+        assert!(!matches("(star == 5 or star == 4) and level == 20", &low_level_5));
+        assert!(matches("star == 4 or (star == 5 and (level == 0 or level == 20))", &low_level_5));
+    }
+
+    #[test]
+    fn not() {
+        let emblem = artifact(ArtifactSetName::EmblemOfSeveredFate, 5, 20);
+        assert!(!matches("not set == EmblemOfSeveredFate", &emblem));
+        assert!(matches("not set == PaleFlame", &emblem));
+        assert!(matches("set != PaleFlame", &emblem));
+        // not only applies to the condition right after it
+        assert!(!matches("not star == 4 and level == 16", &emblem));
+        assert!(matches("not (star == 4 and level == 16)", &emblem));
+        assert!(matches("not not sub == critRate_", &emblem));
+    }
+
+    #[test]
+    fn bad_input() {
+        let inputs = [
+            "",
+            "star",
+            "star ==",
+            "star == five",
+            "star == 5 and",
+            "(star == 5",
+            "star == 5)",
+            "star in [4, 5",
+            "colour == red",
+            "slot == hat",
+            "set > PaleFlame",
+            "level = 20 !",
+            "star == 5 $",
+        ];
+        for s in inputs.iter() {
+            assert!(ArtifactFilter::parse(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn bounds() {
+        let bounds = |s: &str| {
+            let filter = ArtifactFilter::parse(s).unwrap();
+            (filter.min_star(), filter.min_level())
+        };
+        assert_eq!(bounds("star >= 4 and level > 15"), (Some(4), Some(16)));
+        assert_eq!(bounds("star == 5 and (level >= 16 or level == 20)"), (Some(5), None));
+        assert_eq!(bounds("star == 5 or star == 4"), (None, None));
+        assert_eq!(bounds("star in [4, 5]"), (None, None));
+        assert_eq!(bounds("star == 5 and level == 20 or set == PaleFlame"), (None, None));
+        assert_eq!(bounds("not star < 5"), (None, None));
+        assert_eq!(bounds("star <= 4"), (None, None));
+    }
+}
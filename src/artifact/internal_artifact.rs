@@ -1,5 +1,4 @@
-use regex::Regex;
-use std::hash::{Hash, Hasher};
+use std::fmt;
 use edit_distance;
 
 #[derive(Debug, Hash, Clone, PartialEq, Eq)]
@@ -72,12 +71,28 @@ pub enum ArtifactSetName {
     ShimenawaReminiscence,
     HuskOfOpulentDreams,
     OceanHuedClam,
+    // see UnknownNames in the scanner
+    Unknown { raw_title: String },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub enum StatUnit {
+    Percent,
+    Flat,
+}
+
+// Fixed point stat value keeping the single decimal digit shown in game,
+// e.g. "46.6%" is stored as 466 and "4,780" as 47800
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub struct StatValue {
+    pub tenths: i64,
+    pub unit: StatUnit,
+}
+
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
 pub struct ArtifactStat {
     pub name: ArtifactStatName,
-    pub value: f64,
+    pub value: StatValue,
 }
 
 #[derive(Debug, Hash, Clone, PartialEq, Eq)]
@@ -94,28 +109,69 @@ pub struct InternalArtifact {
     pub equip: Option<String>,
 }
 
-impl Hash for ArtifactStat {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.name.hash(state);
-        let v = (self.value * 1000.0) as i32;
-        v.hash(state);
+impl StatValue {
+    pub fn new(tenths: i64, unit: StatUnit) -> StatValue {
+        StatValue { tenths, unit }
     }
-}
 
-impl PartialEq for ArtifactStat {
-    fn eq(&self, other: &Self) -> bool {
-        if self.name != other.name {
-            return false;
+    // e.g. "46.6%", "4,780"
+    pub fn from_zh_cn(s: &str) -> Option<StatValue> {
+        let unit = if s.ends_with('%') { StatUnit::Percent } else { StatUnit::Flat };
+        let digits = s.trim_end_matches('%').replace(",", "");
+
+        let mut parts = digits.splitn(2, '.');
+        let int_part = parts.next()?;
+        let frac_part = parts.next().unwrap_or("0");
+        if int_part.is_empty() || frac_part.len() != 1 {
+            return None;
+        }
+        if !int_part.chars().chain(frac_part.chars()).all(|c| c.is_ascii_digit()) {
+            return None;
         }
 
-        let v1 = (self.value * 1000.0) as i32;
-        let v2 = (other.value * 1000.0) as i32;
+        let tenths = int_part.parse::<i64>().ok()? * 10 + frac_part.parse::<i64>().ok()?;
+        Some(StatValue { tenths, unit })
+    }
 
-        v1 == v2
+    // From the number shown in game, e.g. 46.6 for "46.6%"
+    pub fn from_display(value: f64, unit: StatUnit) -> StatValue {
+        StatValue {
+            tenths: (value * 10.0).round() as i64,
+            unit,
+        }
+    }
+
+    // From a number where percentages are fractions, e.g. 0.466 for "46.6%"
+    pub fn from_f64(value: f64, unit: StatUnit) -> StatValue {
+        match unit {
+            StatUnit::Percent => StatValue::from_display(value * 100.0, unit),
+            StatUnit::Flat => StatValue::from_display(value, unit),
+        }
+    }
+
+    // The number shown in game, e.g. 46.6 for "46.6%"
+    pub fn display_value(&self) -> f64 {
+        self.tenths as f64 / 10.0
+    }
+
+    // Percentages as fractions, e.g. 0.466 for "46.6%"
+    pub fn as_f64(&self) -> f64 {
+        match self.unit {
+            StatUnit::Percent => self.tenths as f64 / 1000.0,
+            StatUnit::Flat => self.tenths as f64 / 10.0,
+        }
     }
 }
 
-impl Eq for ArtifactStat {}
+impl fmt::Display for StatValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.unit {
+            StatUnit::Percent => write!(f, "{}.{}%", self.tenths / 10, self.tenths % 10),
+            StatUnit::Flat if self.tenths % 10 == 0 => write!(f, "{}", self.tenths / 10),
+            StatUnit::Flat => write!(f, "{}.{}", self.tenths / 10, self.tenths % 10),
+        }
+    }
+}
 
 impl ArtifactStatName {
     pub fn from_zh_cn(name: &str, is_percentage: bool) -> Option<ArtifactStatName> {
@@ -140,6 +196,277 @@ impl ArtifactStatName {
     }
 }
 
+impl InternalArtifact {
+    pub fn sub_stats(&self) -> Vec<&ArtifactStat> {
+        let mut ret: Vec<&ArtifactStat> = Vec::new();
+        for s in [&self.sub_stat_1, &self.sub_stat_2, &self.sub_stat_3, &self.sub_stat_4].iter() {
+            if let Some(stat) = s {
+                ret.push(stat);
+            }
+        }
+        ret
+    }
+
+    // Stable across scans as long as the artifact is not upgraded, equip is left out
+    // since it changes when the artifact is moved to another character
+    pub fn id(&self) -> String {
+        let set_name = match self.set_name {
+            ArtifactSetName::Unknown { ref raw_title } => raw_title.clone(),
+            ref name => format!("{:?}", name),
+        };
+        let mut key = format!(
+            "{}|{:?}|{}|{}|{:?}={}",
+            set_name, self.slot, self.star, self.level, self.main_stat.name, self.main_stat.value.tenths
+        );
+        for stat in self.sub_stats() {
+            key += &format!("|{:?}={}", stat.name, stat.value.tenths);
+        }
+
+        // 64 bit FNV-1a, std's hasher is not guaranteed to be stable between releases
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for b in key.bytes() {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}", hash)
+    }
+
+    // Things that cannot happen in game, usually caused by a wrong recognition
+    pub fn validate(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = Vec::new();
+
+        if let ArtifactSetName::Unknown { ref raw_title } = self.set_name {
+            warnings.push(format!("未知套装：{}", raw_title));
+        }
+
+        let max_level = match self.star {
+            5 => 20,
+            4 => 16,
+            3 => 12,
+            _ => 4,
+        };
+        if self.level > max_level {
+            warnings.push(format!("{}星圣遗物等级不应超过{}", self.star, max_level));
+        }
+
+        // other tools may round main stats differently, allow 1% off
+        if let Some(expected) = self.main_stat.name.main_stat_value(self.star, self.level) {
+            let tolerance = (expected.tenths / 100).max(1);
+            if (expected.tenths - self.main_stat.value.tenths).abs() > tolerance {
+                warnings.push(format!("主词条数值{}与等级不符，应约为{}", self.main_stat.value, expected));
+            }
+        }
+
+        // 3-5 star artifacts start with (star - 2) or (star - 1) sub stats and gain one every 4 levels
+        let sub_stats = self.sub_stats();
+        if self.star >= 3 {
+            let min = (self.star - 2 + self.level / 4).min(4) as usize;
+            let max = (self.star - 1 + self.level / 4).min(4) as usize;
+            if sub_stats.len() < min || sub_stats.len() > max {
+                let expected = if min == max { min.to_string() } else { format!("{}~{}", min, max) };
+                warnings.push(format!("+{}时副词条数量应为{}，识别到{}条", self.level, expected, sub_stats.len()));
+            }
+        }
+
+        for (i, stat) in sub_stats.iter().enumerate() {
+            if stat.name == self.main_stat.name {
+                warnings.push(format!("副词条{:?}与主词条相同", stat.name));
+            }
+            if sub_stats[..i].iter().any(|s| s.name == stat.name) {
+                warnings.push(format!("副词条{:?}重复", stat.name));
+            }
+        }
+
+        warnings
+    }
+}
+
+// e.g. "EmblemOfSeveredFate Goblet 5* +20 CryoBonus+46.6% [Critical+10.5%, Hp+299]"
+impl fmt::Display for InternalArtifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let subs = self.sub_stats().iter()
+            .map(|s| format!("{:?}+{}", s.name, s.value))
+            .collect::<Vec<String>>()
+            .join(", ");
+        match self.set_name {
+            ArtifactSetName::Unknown { ref raw_title } => write!(f, "Unknown({})", raw_title)?,
+            ref name => write!(f, "{:?}", name)?,
+        }
+        write!(
+            f, " {:?} {}* +{} {:?}+{} [{}]",
+            self.slot, self.star, self.level,
+            self.main_stat.name, self.main_stat.value,
+            subs
+        )
+    }
+}
+
+impl ArtifactStatName {
+    pub fn is_percentage(&self) -> bool {
+        self.unit() == StatUnit::Percent
+    }
+
+    pub fn unit(&self) -> StatUnit {
+        match self {
+            ArtifactStatName::Atk
+            | ArtifactStatName::ElementalMastery
+            | ArtifactStatName::Hp
+            | ArtifactStatName::Def => StatUnit::Flat,
+            _ => StatUnit::Percent,
+        }
+    }
+}
+
+// Main stat values shown in game from level 0 to max level, percentages as shown, e.g. 46.6 for "46.6%"
+const MAIN_5_HP: [f64; 21] = [
+    717.0, 920.0, 1123.0, 1326.0, 1530.0, 1733.0, 1936.0, 2139.0, 2342.0, 2545.0, 2749.0,
+    2952.0, 3155.0, 3358.0, 3561.0, 3764.0, 3967.0, 4171.0, 4374.0, 4577.0, 4780.0,
+];
+const MAIN_5_ATK: [f64; 21] = [
+    47.0, 60.0, 73.0, 86.0, 100.0, 113.0, 126.0, 139.0, 152.0, 166.0, 179.0,
+    192.0, 205.0, 219.0, 232.0, 245.0, 258.0, 272.0, 285.0, 298.0, 311.0,
+];
+// hp%, atk% and elemental bonus
+const MAIN_5_PERCENT: [f64; 21] = [
+    7.0, 9.0, 11.0, 12.9, 14.9, 16.9, 18.9, 20.9, 22.8, 24.8, 26.8,
+    28.8, 30.8, 32.8, 34.7, 36.7, 38.7, 40.7, 42.7, 44.6, 46.6,
+];
+// def% and physical bonus
+const MAIN_5_DEF_PERCENT: [f64; 21] = [
+    8.7, 11.2, 13.7, 16.2, 18.6, 21.1, 23.6, 26.1, 28.6, 31.0, 33.5,
+    36.0, 38.5, 40.9, 43.4, 45.9, 48.4, 50.8, 53.3, 55.8, 58.3,
+];
+const MAIN_5_EM: [f64; 21] = [
+    28.0, 36.0, 44.0, 52.0, 60.0, 68.0, 76.0, 84.0, 91.0, 99.0, 107.0,
+    115.0, 123.0, 131.0, 139.0, 147.0, 155.0, 163.0, 171.0, 179.0, 187.0,
+];
+const MAIN_5_RECHARGE: [f64; 21] = [
+    7.8, 10.0, 12.2, 14.4, 16.6, 18.8, 21.0, 23.2, 25.4, 27.6, 29.8,
+    32.0, 34.2, 36.4, 38.6, 40.8, 43.0, 45.2, 47.4, 49.6, 51.8,
+];
+const MAIN_5_CRITICAL: [f64; 21] = [
+    4.7, 6.0, 7.3, 8.6, 9.9, 11.3, 12.6, 13.9, 15.2, 16.6, 17.9,
+    19.2, 20.5, 21.8, 23.2, 24.5, 25.8, 27.1, 28.4, 29.8, 31.1,
+];
+const MAIN_5_CRITICAL_DAMAGE: [f64; 21] = [
+    9.3, 12.0, 14.6, 17.3, 19.9, 22.5, 25.2, 27.8, 30.5, 33.1, 35.7,
+    38.4, 41.0, 43.7, 46.3, 49.0, 51.6, 54.2, 56.9, 59.5, 62.2,
+];
+const MAIN_5_HEALING: [f64; 21] = [
+    5.4, 6.9, 8.4, 10.0, 11.5, 13.0, 14.5, 16.1, 17.6, 19.1, 20.6,
+    22.1, 23.7, 25.2, 26.7, 28.2, 29.8, 31.3, 32.8, 34.3, 35.9,
+];
+
+const MAIN_4_HP: [f64; 17] = [
+    645.0, 828.0, 1011.0, 1194.0, 1377.0, 1559.0, 1742.0, 1925.0, 2108.0,
+    2291.0, 2474.0, 2657.0, 2839.0, 3022.0, 3205.0, 3388.0, 3571.0,
+];
+const MAIN_4_ATK: [f64; 17] = [
+    42.0, 54.0, 66.0, 78.0, 90.0, 102.0, 113.0, 125.0, 137.0,
+    149.0, 161.0, 173.0, 185.0, 197.0, 209.0, 221.0, 232.0,
+];
+const MAIN_4_PERCENT: [f64; 17] = [
+    6.3, 8.1, 9.9, 11.6, 13.4, 15.2, 17.0, 18.8, 20.6,
+    22.3, 24.1, 25.9, 27.7, 29.5, 31.3, 33.0, 34.8,
+];
+const MAIN_4_DEF_PERCENT: [f64; 17] = [
+    7.9, 10.1, 12.3, 14.6, 16.8, 19.0, 21.2, 23.5, 25.7,
+    27.9, 30.2, 32.4, 34.6, 36.8, 39.1, 41.3, 43.5,
+];
+const MAIN_4_EM: [f64; 17] = [
+    25.0, 32.0, 39.0, 47.0, 54.0, 61.0, 68.0, 75.0, 82.0,
+    89.0, 97.0, 104.0, 111.0, 118.0, 125.0, 132.0, 139.0,
+];
+const MAIN_4_RECHARGE: [f64; 17] = [
+    7.0, 9.0, 11.0, 12.9, 14.9, 16.9, 18.9, 20.9, 22.8,
+    24.8, 26.8, 28.8, 30.8, 32.8, 34.7, 36.7, 38.7,
+];
+const MAIN_4_CRITICAL: [f64; 17] = [
+    4.2, 5.4, 6.6, 7.8, 9.0, 10.1, 11.3, 12.5, 13.7,
+    14.9, 16.1, 17.3, 18.5, 19.7, 20.8, 22.0, 23.2,
+];
+const MAIN_4_CRITICAL_DAMAGE: [f64; 17] = [
+    8.4, 10.8, 13.1, 15.5, 17.9, 20.3, 22.7, 25.0, 27.4,
+    29.8, 32.2, 34.5, 36.9, 39.3, 41.7, 44.1, 46.4,
+];
+const MAIN_4_HEALING: [f64; 17] = [
+    4.8, 6.2, 7.6, 8.9, 10.3, 11.7, 13.1, 14.4, 15.8,
+    17.2, 18.6, 19.9, 21.3, 22.7, 24.1, 25.4, 26.8,
+];
+
+const MAIN_3_HP: [f64; 13] = [
+    430.0, 552.0, 674.0, 796.0, 918.0, 1040.0, 1162.0, 1283.0, 1405.0, 1527.0, 1649.0, 1771.0, 1893.0,
+];
+const MAIN_3_ATK: [f64; 13] = [
+    28.0, 36.0, 44.0, 52.0, 60.0, 68.0, 76.0, 84.0, 91.0, 99.0, 107.0, 115.0, 123.0,
+];
+const MAIN_3_PERCENT: [f64; 13] = [
+    5.2, 6.7, 8.2, 9.7, 11.2, 12.7, 14.2, 15.6, 17.1, 18.6, 20.1, 21.6, 23.1,
+];
+const MAIN_3_DEF_PERCENT: [f64; 13] = [
+    6.6, 8.4, 10.3, 12.1, 14.0, 15.8, 17.7, 19.6, 21.4, 23.3, 25.1, 27.0, 28.8,
+];
+const MAIN_3_EM: [f64; 13] = [
+    21.0, 27.0, 33.0, 39.0, 45.0, 51.0, 57.0, 63.0, 69.0, 75.0, 80.0, 86.0, 92.0,
+];
+const MAIN_3_RECHARGE: [f64; 13] = [
+    5.8, 7.5, 9.1, 10.8, 12.4, 14.1, 15.7, 17.4, 19.0, 20.7, 22.3, 24.0, 25.6,
+];
+const MAIN_3_CRITICAL: [f64; 13] = [
+    3.5, 4.5, 5.5, 6.5, 7.5, 8.4, 9.4, 10.4, 11.4, 12.4, 13.4, 14.4, 15.4,
+];
+const MAIN_3_CRITICAL_DAMAGE: [f64; 13] = [
+    7.0, 9.0, 11.0, 12.9, 14.9, 16.9, 18.9, 20.9, 22.8, 24.8, 26.8, 28.8, 30.8,
+];
+const MAIN_3_HEALING: [f64; 13] = [
+    4.0, 5.2, 6.3, 7.5, 8.6, 9.8, 11.0, 12.1, 13.3, 14.4, 15.6, 16.8, 17.9,
+];
+
+impl ArtifactStatName {
+    fn main_stat_table(&self, star: u32) -> Option<&'static [f64]> {
+        let table: &'static [f64] = match (star, self) {
+            (_, ArtifactStatName::Def) => return None,
+            (5, ArtifactStatName::Hp) => &MAIN_5_HP,
+            (5, ArtifactStatName::Atk) => &MAIN_5_ATK,
+            (5, ArtifactStatName::DefPercentage) | (5, ArtifactStatName::PhysicalBonus) => &MAIN_5_DEF_PERCENT,
+            (5, ArtifactStatName::ElementalMastery) => &MAIN_5_EM,
+            (5, ArtifactStatName::Recharge) => &MAIN_5_RECHARGE,
+            (5, ArtifactStatName::Critical) => &MAIN_5_CRITICAL,
+            (5, ArtifactStatName::CriticalDamage) => &MAIN_5_CRITICAL_DAMAGE,
+            (5, ArtifactStatName::HealingBonus) => &MAIN_5_HEALING,
+            (5, _) => &MAIN_5_PERCENT,
+            (4, ArtifactStatName::Hp) => &MAIN_4_HP,
+            (4, ArtifactStatName::Atk) => &MAIN_4_ATK,
+            (4, ArtifactStatName::DefPercentage) | (4, ArtifactStatName::PhysicalBonus) => &MAIN_4_DEF_PERCENT,
+            (4, ArtifactStatName::ElementalMastery) => &MAIN_4_EM,
+            (4, ArtifactStatName::Recharge) => &MAIN_4_RECHARGE,
+            (4, ArtifactStatName::Critical) => &MAIN_4_CRITICAL,
+            (4, ArtifactStatName::CriticalDamage) => &MAIN_4_CRITICAL_DAMAGE,
+            (4, ArtifactStatName::HealingBonus) => &MAIN_4_HEALING,
+            (4, _) => &MAIN_4_PERCENT,
+            (3, ArtifactStatName::Hp) => &MAIN_3_HP,
+            (3, ArtifactStatName::Atk) => &MAIN_3_ATK,
+            (3, ArtifactStatName::DefPercentage) | (3, ArtifactStatName::PhysicalBonus) => &MAIN_3_DEF_PERCENT,
+            (3, ArtifactStatName::ElementalMastery) => &MAIN_3_EM,
+            (3, ArtifactStatName::Recharge) => &MAIN_3_RECHARGE,
+            (3, ArtifactStatName::Critical) => &MAIN_3_CRITICAL,
+            (3, ArtifactStatName::CriticalDamage) => &MAIN_3_CRITICAL_DAMAGE,
+            (3, ArtifactStatName::HealingBonus) => &MAIN_3_HEALING,
+            (3, _) => &MAIN_3_PERCENT,
+            _ => return None,
+        };
+        Some(table)
+    }
+
+    // Main stat value of an artifact as displayed in game, or None if the
+    // stat can not be a main stat of the given rarity.
+    pub fn main_stat_value(&self, star: u32, level: u32) -> Option<StatValue> {
+        let table = self.main_stat_table(star)?;
+        let value = *table.get(level as usize)?;
+        Some(StatValue::from_display(value, self.unit()))
+    }
+}
+
 impl ArtifactStat {
     // e.g "生命值+4,123", "暴击率+10%"
     pub fn from_zh_cn_raw(s: &str) -> Option<ArtifactStat> {
@@ -148,18 +475,12 @@ impl ArtifactStat {
             return None;
         }
 
-        let is_percentage = temp[1].contains("%");
-        let stat_name = match ArtifactStatName::from_zh_cn(temp[0], is_percentage) {
+        let value = StatValue::from_zh_cn(temp[1])?;
+        let stat_name = match ArtifactStatName::from_zh_cn(temp[0], value.unit == StatUnit::Percent) {
             Some(v) => v,
             None => return None,
         };
 
-        let re = Regex::new("[%,]").unwrap();
-        let mut value = re.replace_all(temp[1], "").parse::<f64>().unwrap();
-        if is_percentage {
-            value /= 100.0;
-        }
-
         Some(ArtifactStat {
             name: stat_name,
             value,
@@ -226,6 +547,16 @@ pub fn get_real_artifact_name_chs(raw: &str) -> Option<String> {
     }
 }
 
+// The known title `raw` is or is one misread character away from, None if there is no such title or several
+pub fn correct_artifact_name_chs(raw: &str) -> Option<String> {
+    let name = get_real_artifact_name_chs(raw)?;
+    if edit_distance::edit_distance(&name, raw) <= 1 {
+        Some(name)
+    } else {
+        None
+    }
+}
+
 impl ArtifactSetName {
     pub fn from_zh_cn(s: &str) -> Option<ArtifactSetName> {
         // let s = match get_real_artifact_name_chs(s) {
@@ -412,6 +743,36 @@ impl ArtifactSetName {
 }
 
 impl ArtifactSlot {
+    // Guess the slot of an artifact whose title is not recognized,
+    // first from the main stat, then from the last character of the title
+    pub fn guess(title: &str, main_stat: &ArtifactStatName) -> Option<ArtifactSlot> {
+        match main_stat {
+            ArtifactStatName::Hp => return Some(ArtifactSlot::Flower),
+            ArtifactStatName::Atk => return Some(ArtifactSlot::Feather),
+            ArtifactStatName::Recharge => return Some(ArtifactSlot::Sand),
+            ArtifactStatName::ElectroBonus
+            | ArtifactStatName::PyroBonus
+            | ArtifactStatName::HydroBonus
+            | ArtifactStatName::CryoBonus
+            | ArtifactStatName::AnemoBonus
+            | ArtifactStatName::GeoBonus
+            | ArtifactStatName::PhysicalBonus => return Some(ArtifactSlot::Goblet),
+            ArtifactStatName::Critical
+            | ArtifactStatName::CriticalDamage
+            | ArtifactStatName::HealingBonus => return Some(ArtifactSlot::Head),
+            _ => (),
+        }
+
+        match title.chars().last()? {
+            '花' => Some(ArtifactSlot::Flower),
+            '羽' | '翎' | '矢' => Some(ArtifactSlot::Feather),
+            '时' | '晷' | '刻' | '计' | '表' | '漏' | '钟' | '沙' | '谣' | '贝' => Some(ArtifactSlot::Sand),
+            '杯' | '盏' | '壶' | '樽' | '爵' | '瓢' | '皿' | '瓮' => Some(ArtifactSlot::Goblet),
+            '冠' | '面' | '帽' | '兜' | '笠' | '巾' | '带' | '鍪' => Some(ArtifactSlot::Head),
+            _ => None,
+        }
+    }
+
     pub fn from_zh_cn(s: &str) -> Option<ArtifactSlot> {
         // let s = match get_real_artifact_name_chs(s) {
         //     Some(v) => v,
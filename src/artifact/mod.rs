@@ -1 +1,3 @@
-pub mod internal_artifact;
\ No newline at end of file
+pub mod internal_artifact;
+pub mod diff;
+pub mod filter;
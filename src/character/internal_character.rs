@@ -0,0 +1,122 @@
+use std::fmt;
+use edit_distance;
+
+// Chinese names and GOOD keys of the characters known to this version of yas,
+// the names are also the ones shown in "X已装备"
+const CHARACTERS: [(&str, &str); 46] = [
+    ("旅行者", "Traveler"),
+    ("安柏", "Amber"),
+    ("凯亚", "Kaeya"),
+    ("丽莎", "Lisa"),
+    ("琴", "Jean"),
+    ("芭芭拉", "Barbara"),
+    ("迪卢克", "Diluc"),
+    ("雷泽", "Razor"),
+    ("温迪", "Venti"),
+    ("可莉", "Klee"),
+    ("班尼特", "Bennett"),
+    ("诺艾尔", "Noelle"),
+    ("菲谢尔", "Fischl"),
+    ("砂糖", "Sucrose"),
+    ("莫娜", "Mona"),
+    ("迪奥娜", "Diona"),
+    ("阿贝多", "Albedo"),
+    ("罗莎莉亚", "Rosaria"),
+    ("优菈", "Eula"),
+    ("香菱", "Xiangling"),
+    ("北斗", "Beidou"),
+    ("行秋", "Xingqiu"),
+    ("凝光", "Ningguang"),
+    ("重云", "Chongyun"),
+    ("刻晴", "Keqing"),
+    ("七七", "Qiqi"),
+    ("达达利亚", "Tartaglia"),
+    ("钟离", "Zhongli"),
+    ("辛焱", "Xinyan"),
+    ("甘雨", "Ganyu"),
+    ("魈", "Xiao"),
+    ("胡桃", "HuTao"),
+    ("烟绯", "Yanfei"),
+    ("枫原万叶", "KaedeharaKazuha"),
+    ("神里绫华", "KamisatoAyaka"),
+    ("宵宫", "Yoimiya"),
+    ("早柚", "Sayu"),
+    ("雷电将军", "RaidenShogun"),
+    ("九条裟罗", "KujouSara"),
+    ("埃洛伊", "Aloy"),
+    ("珊瑚宫心海", "SangonomiyaKokomi"),
+    ("托马", "Thoma"),
+    ("荒泷一斗", "AratakiItto"),
+    ("五郎", "Gorou"),
+    ("申鹤", "Shenhe"),
+    ("云堇", "YunJin"),
+];
+
+// Exact name first, then a name with a single misread character
+pub fn character_key(name: &str) -> Option<&'static str> {
+    if let Some((_, key)) = CHARACTERS.iter().find(|(zh, _)| *zh == name) {
+        return Some(key);
+    }
+    // short names are too close to each other to guess
+    let close: Vec<&'static str> = CHARACTERS.iter()
+        .filter(|(zh, _)| zh.chars().count() >= 3 && edit_distance::edit_distance(zh, name) <= 1)
+        .map(|(_, key)| *key)
+        .collect();
+    if close.len() == 1 {
+        Some(close[0])
+    } else {
+        None
+    }
+}
+
+pub fn character_name(key: &str) -> Option<&'static str> {
+    CHARACTERS.iter().find(|(_, k)| *k == key).map(|(zh, _)| *zh)
+}
+
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+pub enum CharacterName {
+    // key in CHARACTERS, the same as the GOOD character key
+    Known(&'static str),
+    // see UnknownNames in the scanner
+    Unknown { raw_name: String },
+}
+
+impl CharacterName {
+    pub fn from_zh_cn(s: &str) -> Option<CharacterName> {
+        character_key(s).map(CharacterName::Known)
+    }
+
+    // "Unknown" for characters not in the table
+    pub fn key(&self) -> &'static str {
+        match self {
+            CharacterName::Known(key) => key,
+            CharacterName::Unknown { .. } => "Unknown",
+        }
+    }
+}
+
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+pub struct InternalCharacter {
+    pub name: CharacterName,
+    pub level: u32,
+    pub ascension: u32,
+    pub constellation: u32,
+    // without the bonus of constellations
+    pub talent_auto: u32,
+    pub talent_skill: u32,
+    pub talent_burst: u32,
+}
+
+impl fmt::Display for InternalCharacter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            CharacterName::Known(key) => write!(f, "{}", key)?,
+            CharacterName::Unknown { ref raw_name } => write!(f, "Unknown({})", raw_name)?,
+        }
+        write!(
+            f, " {}/{} C{} {}/{}/{}",
+            self.level, crate::common::level::max_level(self.ascension), self.constellation,
+            self.talent_auto, self.talent_skill, self.talent_burst
+        )
+    }
+}
@@ -0,0 +1,24 @@
+// Level cap of each ascension phase, the same for characters and weapons
+const ASCENSION_MAX_LEVEL: [u32; 7] = [20, 40, 50, 60, 70, 80, 90];
+
+// e.g. "80/90" is level 80 after the 6th ascension, "80/80" after the 5th
+pub fn ascension_from_level(level: u32, max_level: u32) -> Option<u32> {
+    let ascension = ASCENSION_MAX_LEVEL.iter().position(|m| *m == max_level)?;
+    if level > max_level || (ascension > 0 && level < ASCENSION_MAX_LEVEL[ascension - 1]) {
+        return None;
+    }
+    Some(ascension as u32)
+}
+
+pub fn max_level(ascension: u32) -> u32 {
+    ASCENSION_MAX_LEVEL[(ascension as usize).min(ASCENSION_MAX_LEVEL.len() - 1)]
+}
+
+// "Lv.80/90", "等级80/90" or "80/90"
+pub fn parse_level(s: &str) -> Option<(u32, u32)> {
+    let s = s.trim_start_matches(|c: char| !c.is_ascii_digit());
+    let mut parts = s.splitn(2, '/');
+    let level = parts.next()?.trim().parse::<u32>().ok()?;
+    let max_level = parts.next()?.trim().parse::<u32>().ok()?;
+    Some((level, max_level))
+}
@@ -9,6 +9,7 @@ use log::{info};
 pub mod utils;
 pub mod buffer;
 pub mod color;
+pub mod level;
 
 #[derive(Debug)]
 pub struct PixelRect {
@@ -106,6 +107,15 @@ impl RawImage {
 }
 
 impl RawCaptureImage {
+    // the captured data is BGRA with the rows bottom up
+    pub fn to_rgb_image(&self) -> RgbImage {
+        let (w, h) = (self.w, self.h);
+        RgbImage::from_fn(w, h, |x, y| {
+            let index = (((h - y - 1) * w + x) * 4) as usize;
+            image::Rgb([self.data[index + 2], self.data[index + 1], self.data[index]])
+        })
+    }
+
     pub fn crop_to_raw_img(&self, rect: &PixelRect) -> RawImage {
         // let now = SystemTime::now();
         let vol = rect.width * rect.height;
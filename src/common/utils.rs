@@ -10,7 +10,8 @@ use std::env::{current_exe, args};
 use log::error;
 use winapi::shared::windef::{HWND, POINT as WinPoint, RECT as WinRect};
 use winapi::um::winuser::{
-    ClientToScreen, FindWindowW, GetAsyncKeyState, GetClientRect, VK_RBUTTON,
+    ClientToScreen, FindWindowW, GetAsyncKeyState, GetClientRect, VK_ESCAPE, VK_F1, VK_MBUTTON, VK_RBUTTON,
+    VK_SPACE, VK_XBUTTON1, VK_XBUTTON2,
 };
 
 use crate::common::PixelRect;
@@ -120,9 +121,31 @@ pub fn is_admin() -> bool {
     unsafe { is_admin_unsafe() }
 }
 
-pub fn is_rmb_down() -> bool {
+// Virtual-key code of a key or mouse button which can stop a scan
+pub fn key_code(name: &str) -> Option<i32> {
+    let code = match name {
+        "rmb" => VK_RBUTTON,
+        "mmb" => VK_MBUTTON,
+        "x1" => VK_XBUTTON1,
+        "x2" => VK_XBUTTON2,
+        "esc" => VK_ESCAPE,
+        "space" => VK_SPACE,
+        _ => {
+            // "f1" to "f12"
+            let n = name.strip_prefix('f')?.parse::<i32>().ok()?;
+            if n < 1 || n > 12 {
+                return None;
+            }
+            VK_F1 + n - 1
+        }
+    };
+    Some(code)
+}
+
+// Whether the key was pressed since the last call
+pub fn is_key_down(key: i32) -> bool {
     unsafe {
-        let state = GetAsyncKeyState(VK_RBUTTON);
+        let state = GetAsyncKeyState(key);
         if state == 0 {
             return false;
         }
@@ -0,0 +1,172 @@
+use std::io::Write;
+
+use crate::artifact::internal_artifact::{ArtifactSetName, ArtifactStat, InternalArtifact};
+use crate::expo::exporter::{io_error, Exporter};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CsvHeaderStyle {
+    Snake,
+    Camel,
+    ZhCn,
+}
+
+impl CsvHeaderStyle {
+    pub fn from_name(s: &str) -> Option<CsvHeaderStyle> {
+        match s {
+            "snake" => Some(CsvHeaderStyle::Snake),
+            "camel" => Some(CsvHeaderStyle::Camel),
+            "zh" => Some(CsvHeaderStyle::ZhCn),
+            _ => None,
+        }
+    }
+
+    fn header(&self) -> Vec<String> {
+        let (fixed, sub_name, sub_value, equip): ([&str; 6], &str, &str, &str) = match self {
+            CsvHeaderStyle::Snake => (
+                ["set", "slot", "star", "level", "main_stat", "main_stat_value"],
+                "sub_stat_{}", "sub_stat_{}_value", "equip",
+            ),
+            CsvHeaderStyle::Camel => (
+                ["set", "slot", "star", "level", "mainStat", "mainStatValue"],
+                "subStat{}", "subStat{}Value", "equip",
+            ),
+            CsvHeaderStyle::ZhCn => (
+                ["套装", "部位", "星级", "等级", "主词条", "主词条数值"],
+                "副词条{}", "副词条{}数值", "装备",
+            ),
+        };
+
+        let mut ret: Vec<String> = fixed.iter().map(|s| String::from(*s)).collect();
+        for i in 1..=4 {
+            ret.push(sub_name.replace("{}", &i.to_string()));
+            ret.push(sub_value.replace("{}", &i.to_string()));
+        }
+        ret.push(String::from(equip));
+        ret
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct CsvConfig {
+    pub delimiter: char,
+    pub header: CsvHeaderStyle,
+    // write percentages as fractions, e.g. 0.466 instead of 46.6
+    pub fraction: bool,
+}
+
+impl CsvConfig {
+    pub fn csv() -> CsvConfig {
+        CsvConfig {
+            delimiter: ',',
+            header: CsvHeaderStyle::Snake,
+            fraction: false,
+        }
+    }
+
+    pub fn tsv() -> CsvConfig {
+        CsvConfig {
+            delimiter: '\t',
+            ..CsvConfig::csv()
+        }
+    }
+}
+
+pub struct CsvFormat<'a> {
+    artifacts: &'a [InternalArtifact],
+    config: CsvConfig,
+}
+
+impl<'a> CsvFormat<'a> {
+    pub fn new(results: &'a [InternalArtifact], config: CsvConfig) -> CsvFormat<'a> {
+        CsvFormat {
+            artifacts: results,
+            config,
+        }
+    }
+
+    fn escape(&self, field: &str) -> String {
+        if field.contains(self.config.delimiter) || field.contains('"') || field.contains('\n') {
+            format!("\"{}\"", field.replace("\"", "\"\""))
+        } else {
+            String::from(field)
+        }
+    }
+
+    fn stat_value(&self, stat: &ArtifactStat) -> String {
+        if self.config.fraction {
+            stat.value.as_f64().to_string()
+        } else {
+            stat.value.display_value().to_string()
+        }
+    }
+
+    fn row(&self, artifact: &InternalArtifact) -> Vec<String> {
+        let set_name = match artifact.set_name {
+            ArtifactSetName::Unknown { ref raw_title } => raw_title.clone(),
+            ref name => String::from(name.to_yas()),
+        };
+        let mut ret = vec![
+            set_name,
+            String::from(artifact.slot.to_yas()),
+            artifact.star.to_string(),
+            artifact.level.to_string(),
+            String::from(artifact.main_stat.name.to_yas()),
+            self.stat_value(&artifact.main_stat),
+        ];
+        for sub in [&artifact.sub_stat_1, &artifact.sub_stat_2, &artifact.sub_stat_3, &artifact.sub_stat_4].iter() {
+            match sub {
+                Some(stat) => {
+                    ret.push(String::from(stat.name.to_yas()));
+                    ret.push(self.stat_value(stat));
+                }
+                None => {
+                    ret.push(String::new());
+                    ret.push(String::new());
+                }
+            }
+        }
+        ret.push(artifact.equip.clone().unwrap_or_default());
+        ret
+    }
+
+    pub fn render(&self) -> String {
+        let delimiter = self.config.delimiter.to_string();
+        let mut lines: Vec<String> = Vec::new();
+        lines.push(self.config.header.header().join(&delimiter));
+        for artifact in self.artifacts.iter() {
+            let row = self.row(artifact).iter().map(|f| self.escape(f)).collect::<Vec<String>>();
+            lines.push(row.join(&delimiter));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+// the same exporter serves csv and tsv, which only differ in the delimiter
+pub struct CsvExporter {
+    extension: &'static str,
+    config: CsvConfig,
+}
+
+impl CsvExporter {
+    pub fn new(extension: &'static str, config: CsvConfig) -> CsvExporter {
+        CsvExporter { extension, config }
+    }
+}
+
+impl Exporter for CsvExporter {
+    fn name(&self) -> &'static str {
+        self.extension
+    }
+
+    fn extension(&self) -> &'static str {
+        self.extension
+    }
+
+    fn file_name(&self) -> String {
+        format!("artifacts.{}", self.extension)
+    }
+
+    fn write(&self, results: &[InternalArtifact], w: &mut dyn Write) -> Result<(), String> {
+        w.write_all(CsvFormat::new(results, self.config).render().as_bytes()).map_err(io_error)
+    }
+}
@@ -0,0 +1,187 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use clap::ArgMatches;
+use log::warn;
+
+use crate::artifact::internal_artifact::InternalArtifact;
+use crate::expo::csv::{CsvConfig, CsvExporter, CsvHeaderStyle};
+use crate::expo::good::GOODExporter;
+use crate::expo::html::HtmlExporter;
+use crate::expo::mingyu_lab::MingyuLabExporter;
+use crate::expo::mona_uranai::{MonaExporter, MONA_FORMAT_VERSIONS};
+use crate::expo::yas::{YasExporter, YasScanMetadata};
+use crate::scanner::yas_scanner::YasArtifactRecord;
+
+pub trait Exporter {
+    // used on the command line, e.g. `-f mona`
+    fn name(&self) -> &'static str;
+
+    fn extension(&self) -> &'static str;
+
+    fn file_name(&self) -> String {
+        format!("{}.{}", self.name(), self.extension())
+    }
+
+    fn write(&self, results: &[InternalArtifact], w: &mut dyn Write) -> Result<(), String>;
+
+    // why the artifact is left out by this format, None if it can be written
+    fn unsupported(&self, _artifact: &InternalArtifact) -> Option<String> {
+        None
+    }
+
+    // formats that keep scan information override this, others only see the artifacts
+    fn write_scan(
+        &self,
+        records: &[YasArtifactRecord],
+        _metadata: &YasScanMetadata,
+        w: &mut dyn Write,
+    ) -> Result<(), String> {
+        let results = records.iter().map(|r| r.artifact.clone()).collect::<Vec<_>>();
+        self.write(&results, w)
+    }
+}
+
+pub fn io_error(e: std::io::Error) -> String {
+    e.to_string()
+}
+
+pub fn json_error(e: serde_json::Error) -> String {
+    e.to_string()
+}
+
+// What is done with the results once the scan is over, the scanner doesn't read these
+pub struct ExportOptions {
+    pub csv_header: CsvHeaderStyle,
+    pub csv_fraction: bool,
+    pub mona_version: &'static str,
+    // an existing mona file updated with the results
+    pub merge_into: Option<String>,
+    // the scan history database the results are added to
+    pub db: Option<String>,
+    pub account: Option<String>,
+}
+
+impl ExportOptions {
+    pub fn from_match(matches: &ArgMatches) -> ExportOptions {
+        let mona_version = matches.value_of("mona-version").unwrap_or("1");
+        ExportOptions {
+            csv_header: CsvHeaderStyle::from_name(matches.value_of("csv-header").unwrap_or("snake")).expect("Unreachable"),
+            csv_fraction: matches.is_present("csv-fraction"),
+            mona_version: MONA_FORMAT_VERSIONS.iter().find(|v| **v == mona_version).expect("Unreachable"),
+            merge_into: matches.value_of("merge-into").map(|s| s.to_string()),
+            db: matches.value_of("db").map(|s| s.to_string()),
+            account: matches.value_of("account").map(|s| s.to_string()),
+        }
+    }
+}
+
+impl Default for ExportOptions {
+    fn default() -> ExportOptions {
+        ExportOptions {
+            csv_header: CsvHeaderStyle::Snake,
+            csv_fraction: false,
+            mona_version: "1",
+            merge_into: None,
+            db: None,
+            account: None,
+        }
+    }
+}
+
+pub struct ExporterRegistry {
+    exporters: Vec<Box<dyn Exporter>>,
+}
+
+impl ExporterRegistry {
+    pub fn new() -> ExporterRegistry {
+        ExporterRegistry {
+            exporters: Vec::new(),
+        }
+    }
+
+    // the default exporters, with mona and csv configured by the options
+    pub fn with_options(options: &ExportOptions) -> ExporterRegistry {
+        let mut registry = ExporterRegistry::default();
+        registry.register(Box::new(MonaExporter::new(options.mona_version)));
+        for (name, mut csv_config) in [("csv", CsvConfig::csv()), ("tsv", CsvConfig::tsv())].iter().cloned() {
+            csv_config.header = options.csv_header;
+            csv_config.fraction = options.csv_fraction;
+            registry.register(Box::new(CsvExporter::new(name, csv_config)));
+        }
+        registry
+    }
+
+    // an exporter with the same name is replaced
+    pub fn register(&mut self, exporter: Box<dyn Exporter>) {
+        match self.exporters.iter().position(|e| e.name() == exporter.name()) {
+            Some(i) => self.exporters[i] = exporter,
+            None => self.exporters.push(exporter),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Exporter> {
+        self.exporters.iter().find(|e| e.name() == name).map(|e| e.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.exporters.iter().map(|e| e.name()).collect()
+    }
+}
+
+impl Default for ExporterRegistry {
+    fn default() -> ExporterRegistry {
+        let mut registry = ExporterRegistry::new();
+        registry.register(Box::new(MonaExporter::new("1")));
+        registry.register(Box::new(MingyuLabExporter));
+        registry.register(Box::new(YasExporter));
+        registry.register(Box::new(GOODExporter));
+        registry.register(Box::new(CsvExporter::new("csv", CsvConfig::csv())));
+        registry.register(Box::new(CsvExporter::new("tsv", CsvConfig::tsv())));
+        registry.register(Box::new(HtmlExporter));
+        registry
+    }
+}
+
+// Warn about every artifact the exporter leaves out, returns how many
+pub fn report_unsupported<'a, I>(exporter: &dyn Exporter, artifacts: I) -> usize
+    where I: IntoIterator<Item = &'a InternalArtifact>
+{
+    let mut count = 0;
+    for artifact in artifacts {
+        if let Some(reason) = exporter.unsupported(artifact) {
+            warn!("{}: {} ({})", exporter.name(), artifact, reason);
+            count += 1;
+        }
+    }
+    if count > 0 {
+        warn!("{}个圣遗物无法导出为{}格式", count, exporter.name());
+    }
+    count
+}
+
+fn save_with<F>(path: &Path, write: F) -> Result<(), String>
+    where F: FnOnce(&mut dyn Write) -> Result<(), String>
+{
+    let file = File::create(path).map_err(|e| format!("couldn't create {}: {}", path.display(), e))?;
+    let mut w = BufWriter::new(file);
+    write(&mut w)
+        .and_then(|_| w.flush().map_err(io_error))
+        .map_err(|e| format!("couldn't write to {}: {}", path.display(), e))
+}
+
+pub fn save(exporter: &dyn Exporter, results: &[InternalArtifact], path: &Path) -> Result<(), String> {
+    report_unsupported(exporter, results);
+    save_with(path, |w| exporter.write(results, w))
+}
+
+pub fn save_scan(
+    exporter: &dyn Exporter,
+    records: &[YasArtifactRecord],
+    metadata: &YasScanMetadata,
+    path: &Path,
+) -> Result<(), String> {
+    report_unsupported(exporter, records.iter().map(|r| &r.artifact));
+    save_with(path, |w| exporter.write_scan(records, metadata, w))
+}
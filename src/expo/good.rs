@@ -0,0 +1,494 @@
+use std::fs;
+use std::io::Write;
+
+use log::warn;
+use serde::ser::{Error, Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+
+use crate::artifact::internal_artifact::{
+    ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName, InternalArtifact, StatValue,
+};
+use crate::character::internal_character::{character_key, character_name, CharacterName, InternalCharacter};
+use crate::expo::exporter::{json_error, Exporter};
+use crate::material::internal_material::InternalMaterial;
+use crate::weapon::internal_weapon::{InternalWeapon, WeaponName};
+
+impl ArtifactStatName {
+    pub fn to_good(&self) -> &'static str {
+        match self {
+            ArtifactStatName::HealingBonus => "heal_",
+            ArtifactStatName::CriticalDamage => "critDMG_",
+            ArtifactStatName::Critical => "critRate_",
+            ArtifactStatName::Atk => "atk",
+            ArtifactStatName::AtkPercentage => "atk_",
+            ArtifactStatName::ElementalMastery => "eleMas",
+            ArtifactStatName::Recharge => "enerRech_",
+            ArtifactStatName::HpPercentage => "hp_",
+            ArtifactStatName::Hp => "hp",
+            ArtifactStatName::DefPercentage => "def_",
+            ArtifactStatName::Def => "def",
+            ArtifactStatName::ElectroBonus => "electro_dmg_",
+            ArtifactStatName::PyroBonus => "pyro_dmg_",
+            ArtifactStatName::HydroBonus => "hydro_dmg_",
+            ArtifactStatName::CryoBonus => "cryo_dmg_",
+            ArtifactStatName::AnemoBonus => "anemo_dmg_",
+            ArtifactStatName::GeoBonus => "geo_dmg_",
+            ArtifactStatName::PhysicalBonus => "physical_dmg_",
+        }
+    }
+
+    pub fn from_good(s: &str) -> Option<ArtifactStatName> {
+        match s {
+            "heal_" => Some(ArtifactStatName::HealingBonus),
+            "critDMG_" => Some(ArtifactStatName::CriticalDamage),
+            "critRate_" => Some(ArtifactStatName::Critical),
+            "atk" => Some(ArtifactStatName::Atk),
+            "atk_" => Some(ArtifactStatName::AtkPercentage),
+            "eleMas" => Some(ArtifactStatName::ElementalMastery),
+            "enerRech_" => Some(ArtifactStatName::Recharge),
+            "hp_" => Some(ArtifactStatName::HpPercentage),
+            "hp" => Some(ArtifactStatName::Hp),
+            "def_" => Some(ArtifactStatName::DefPercentage),
+            "def" => Some(ArtifactStatName::Def),
+            "electro_dmg_" => Some(ArtifactStatName::ElectroBonus),
+            "pyro_dmg_" => Some(ArtifactStatName::PyroBonus),
+            "hydro_dmg_" => Some(ArtifactStatName::HydroBonus),
+            "cryo_dmg_" => Some(ArtifactStatName::CryoBonus),
+            "anemo_dmg_" => Some(ArtifactStatName::AnemoBonus),
+            "geo_dmg_" => Some(ArtifactStatName::GeoBonus),
+            "physical_dmg_" => Some(ArtifactStatName::PhysicalBonus),
+            _ => None,
+        }
+    }
+}
+
+impl ArtifactSlot {
+    pub fn to_good(&self) -> &'static str {
+        match self {
+            ArtifactSlot::Flower => "flower",
+            ArtifactSlot::Feather => "plume",
+            ArtifactSlot::Sand => "sands",
+            ArtifactSlot::Goblet => "goblet",
+            ArtifactSlot::Head => "circlet",
+        }
+    }
+
+    pub fn from_good(s: &str) -> Option<ArtifactSlot> {
+        match s {
+            "flower" => Some(ArtifactSlot::Flower),
+            "plume" => Some(ArtifactSlot::Feather),
+            "sands" => Some(ArtifactSlot::Sand),
+            "goblet" => Some(ArtifactSlot::Goblet),
+            "circlet" => Some(ArtifactSlot::Head),
+            _ => None,
+        }
+    }
+}
+
+impl ArtifactSetName {
+    pub fn to_good(&self) -> Option<&'static str> {
+        match self {
+            ArtifactSetName::ArchaicPetra => Some("ArchaicPetra"),
+            ArtifactSetName::HeartOfDepth => Some("HeartOfDepth"),
+            ArtifactSetName::BlizzardStrayer => Some("BlizzardStrayer"),
+            ArtifactSetName::RetracingBolide => Some("RetracingBolide"),
+            ArtifactSetName::NoblesseOblige => Some("NoblesseOblige"),
+            ArtifactSetName::GladiatorFinale => Some("GladiatorsFinale"),
+            ArtifactSetName::MaidenBeloved => Some("MaidenBeloved"),
+            ArtifactSetName::ViridescentVenerer => Some("ViridescentVenerer"),
+            ArtifactSetName::LavaWalker => Some("Lavawalker"),
+            ArtifactSetName::CrimsonWitch => Some("CrimsonWitchOfFlames"),
+            ArtifactSetName::ThunderSmoother => Some("Thundersoother"),
+            ArtifactSetName::ThunderingFury => Some("ThunderingFury"),
+            ArtifactSetName::BloodstainedChivalry => Some("BloodstainedChivalry"),
+            ArtifactSetName::WandererTroupe => Some("WanderersTroupe"),
+            ArtifactSetName::Scholar => Some("Scholar"),
+            ArtifactSetName::Gambler => Some("Gambler"),
+            ArtifactSetName::TinyMiracle => Some("TinyMiracle"),
+            ArtifactSetName::MartialArtist => Some("MartialArtist"),
+            ArtifactSetName::BraveHeart => Some("BraveHeart"),
+            ArtifactSetName::ResolutionOfSojourner => Some("ResolutionOfSojourner"),
+            ArtifactSetName::DefenderWill => Some("DefendersWill"),
+            ArtifactSetName::Berserker => Some("Berserker"),
+            ArtifactSetName::Instructor => Some("Instructor"),
+            ArtifactSetName::Exile => Some("TheExile"),
+            ArtifactSetName::Adventurer => Some("Adventurer"),
+            ArtifactSetName::LuckyDog => Some("LuckyDog"),
+            ArtifactSetName::TravelingDoctor => Some("TravelingDoctor"),
+            ArtifactSetName::PrayersForWisdom => Some("PrayersForWisdom"),
+            ArtifactSetName::PrayersToSpringtime => Some("PrayersToSpringtime"),
+            ArtifactSetName::PrayersForIllumination => Some("PrayersForIllumination"),
+            ArtifactSetName::PrayersForDestiny => Some("PrayersForDestiny"),
+            ArtifactSetName::PaleFlame => Some("PaleFlame"),
+            ArtifactSetName::TenacityOfTheMillelith => Some("TenacityOfTheMillelith"),
+            ArtifactSetName::EmblemOfSeveredFate => Some("EmblemOfSeveredFate"),
+            ArtifactSetName::ShimenawaReminiscence => Some("ShimenawasReminiscence"),
+            ArtifactSetName::HuskOfOpulentDreams => Some("HuskOfOpulentDreams"),
+            ArtifactSetName::OceanHuedClam => Some("OceanHuedClam"),
+
+            // Not supported by GOOD
+            ArtifactSetName::Unknown { .. } => None,
+        }
+    }
+
+    pub fn from_good(s: &str) -> Option<ArtifactSetName> {
+        match s {
+            "ArchaicPetra" => Some(ArtifactSetName::ArchaicPetra),
+            "HeartOfDepth" => Some(ArtifactSetName::HeartOfDepth),
+            "BlizzardStrayer" => Some(ArtifactSetName::BlizzardStrayer),
+            "RetracingBolide" => Some(ArtifactSetName::RetracingBolide),
+            "NoblesseOblige" => Some(ArtifactSetName::NoblesseOblige),
+            "GladiatorsFinale" => Some(ArtifactSetName::GladiatorFinale),
+            "MaidenBeloved" => Some(ArtifactSetName::MaidenBeloved),
+            "ViridescentVenerer" => Some(ArtifactSetName::ViridescentVenerer),
+            "Lavawalker" => Some(ArtifactSetName::LavaWalker),
+            "CrimsonWitchOfFlames" => Some(ArtifactSetName::CrimsonWitch),
+            "Thundersoother" => Some(ArtifactSetName::ThunderSmoother),
+            "ThunderingFury" => Some(ArtifactSetName::ThunderingFury),
+            "BloodstainedChivalry" => Some(ArtifactSetName::BloodstainedChivalry),
+            "WanderersTroupe" => Some(ArtifactSetName::WandererTroupe),
+            "Scholar" => Some(ArtifactSetName::Scholar),
+            "Gambler" => Some(ArtifactSetName::Gambler),
+            "TinyMiracle" => Some(ArtifactSetName::TinyMiracle),
+            "MartialArtist" => Some(ArtifactSetName::MartialArtist),
+            "BraveHeart" => Some(ArtifactSetName::BraveHeart),
+            "ResolutionOfSojourner" => Some(ArtifactSetName::ResolutionOfSojourner),
+            "DefendersWill" => Some(ArtifactSetName::DefenderWill),
+            "Berserker" => Some(ArtifactSetName::Berserker),
+            "Instructor" => Some(ArtifactSetName::Instructor),
+            "TheExile" => Some(ArtifactSetName::Exile),
+            "Adventurer" => Some(ArtifactSetName::Adventurer),
+            "LuckyDog" => Some(ArtifactSetName::LuckyDog),
+            "TravelingDoctor" => Some(ArtifactSetName::TravelingDoctor),
+            "PrayersForWisdom" => Some(ArtifactSetName::PrayersForWisdom),
+            "PrayersToSpringtime" => Some(ArtifactSetName::PrayersToSpringtime),
+            "PrayersForIllumination" => Some(ArtifactSetName::PrayersForIllumination),
+            "PrayersForDestiny" => Some(ArtifactSetName::PrayersForDestiny),
+            "PaleFlame" => Some(ArtifactSetName::PaleFlame),
+            "TenacityOfTheMillelith" => Some(ArtifactSetName::TenacityOfTheMillelith),
+            "EmblemOfSeveredFate" => Some(ArtifactSetName::EmblemOfSeveredFate),
+            "ShimenawasReminiscence" => Some(ArtifactSetName::ShimenawaReminiscence),
+            "HuskOfOpulentDreams" => Some(ArtifactSetName::HuskOfOpulentDreams),
+            "OceanHuedClam" => Some(ArtifactSetName::OceanHuedClam),
+            _ => None,
+        }
+    }
+}
+
+struct GOODStat<'a> {
+    stat: &'a ArtifactStat,
+}
+
+impl<'a> Serialize for GOODStat<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(Some(2))?;
+        root.serialize_entry("key", self.stat.name.to_good())?;
+        // percentages are written as shown in game, e.g. 46.6
+        root.serialize_entry("value", &self.stat.value.display_value())?;
+        root.end()
+    }
+}
+
+struct GOODArtifact<'a> {
+    artifact: &'a InternalArtifact,
+}
+
+impl<'a> Serialize for GOODArtifact<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let artifact = self.artifact;
+        let location = good_location(&artifact.equip);
+        let substats: Vec<GOODStat> = [&artifact.sub_stat_1, &artifact.sub_stat_2, &artifact.sub_stat_3, &artifact.sub_stat_4]
+            .iter()
+            .filter_map(|s| s.as_ref())
+            .map(|stat| GOODStat { stat })
+            .collect();
+
+        let mut root = serializer.serialize_map(Some(7))?;
+        let set_key = artifact.set_name.to_good()
+            .ok_or_else(|| S::Error::custom(format!("set not supported by GOOD: {:?}", artifact.set_name)))?;
+        root.serialize_entry("setKey", set_key)?;
+        root.serialize_entry("slotKey", artifact.slot.to_good())?;
+        root.serialize_entry("level", &artifact.level)?;
+        root.serialize_entry("rarity", &artifact.star)?;
+        root.serialize_entry("mainStatKey", artifact.main_stat.name.to_good())?;
+        root.serialize_entry("location", location)?;
+        // lock state is not recognized yet, writing false would make importers unlock everything
+        root.serialize_entry("substats", &substats)?;
+        root.end()
+    }
+}
+
+fn good_location(equip: &Option<String>) -> &'static str {
+    match equip {
+        Some(ref name) => match character_key(name) {
+            Some(key) => key,
+            None => {
+                warn!("unknown character: {}", name);
+                ""
+            }
+        },
+        None => "",
+    }
+}
+
+struct GOODWeapon<'a> {
+    weapon: &'a InternalWeapon,
+}
+
+impl<'a> Serialize for GOODWeapon<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let weapon = self.weapon;
+        let mut root = serializer.serialize_map(Some(5))?;
+        root.serialize_entry("key", weapon.name.key())?;
+        root.serialize_entry("level", &weapon.level)?;
+        root.serialize_entry("ascension", &weapon.ascension)?;
+        root.serialize_entry("refinement", &weapon.refinement)?;
+        root.serialize_entry("location", good_location(&weapon.equip))?;
+        // see GOODArtifact about lock
+        root.end()
+    }
+}
+
+struct GOODTalent<'a> {
+    character: &'a InternalCharacter,
+}
+
+impl<'a> Serialize for GOODTalent<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(Some(3))?;
+        root.serialize_entry("auto", &self.character.talent_auto)?;
+        root.serialize_entry("skill", &self.character.talent_skill)?;
+        root.serialize_entry("burst", &self.character.talent_burst)?;
+        root.end()
+    }
+}
+
+struct GOODCharacter<'a> {
+    character: &'a InternalCharacter,
+}
+
+impl<'a> Serialize for GOODCharacter<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let character = self.character;
+        let mut root = serializer.serialize_map(Some(5))?;
+        root.serialize_entry("key", character.name.key())?;
+        root.serialize_entry("level", &character.level)?;
+        root.serialize_entry("constellation", &character.constellation)?;
+        root.serialize_entry("ascension", &character.ascension)?;
+        root.serialize_entry("talent", &GOODTalent { character })?;
+        root.end()
+    }
+}
+
+// GOOD keeps materials as a map from the key to the count
+struct GOODMaterials<'a> {
+    materials: &'a [InternalMaterial],
+}
+
+impl<'a> Serialize for GOODMaterials<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(Some(self.materials.len()))?;
+        for material in self.materials.iter() {
+            root.serialize_entry(&material.key, &material.count)?;
+        }
+        root.end()
+    }
+}
+
+fn unsupported(artifact: &InternalArtifact) -> Option<String> {
+    match artifact.set_name {
+        ArtifactSetName::Unknown { .. } => Some(String::from("未知套装")),
+        _ => None,
+    }
+}
+
+// Every part of a GOOD file is optional, only the scanned ones are written
+pub struct GOODFormat<'a> {
+    artifacts: Option<Vec<GOODArtifact<'a>>>,
+    weapons: Option<Vec<GOODWeapon<'a>>>,
+    characters: Option<Vec<GOODCharacter<'a>>>,
+    materials: Option<GOODMaterials<'a>>,
+}
+
+impl<'a> Serialize for GOODFormat<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(None)?;
+        root.serialize_entry("format", "GOOD")?;
+        root.serialize_entry("version", &1)?;
+        root.serialize_entry("source", "Yas")?;
+        if let Some(ref artifacts) = self.artifacts {
+            root.serialize_entry("artifacts", artifacts)?;
+        }
+        if let Some(ref weapons) = self.weapons {
+            root.serialize_entry("weapons", weapons)?;
+        }
+        if let Some(ref characters) = self.characters {
+            root.serialize_entry("characters", characters)?;
+        }
+        if let Some(ref materials) = self.materials {
+            root.serialize_entry("materials", materials)?;
+        }
+        root.end()
+    }
+}
+
+fn artifact_from_good(value: &Value) -> Result<InternalArtifact, String> {
+    let set_name = value["setKey"].as_str().ok_or("missing setKey")?;
+    let set_name = ArtifactSetName::from_good(set_name).ok_or(format!("unknown set key: {}", set_name))?;
+    let slot = value["slotKey"].as_str().ok_or("missing slotKey")?;
+    let slot = ArtifactSlot::from_good(slot).ok_or(format!("unknown slot key: {}", slot))?;
+    let main_stat_name = value["mainStatKey"].as_str().ok_or("missing mainStatKey")?;
+    let main_stat_name = ArtifactStatName::from_good(main_stat_name)
+        .ok_or(format!("unknown stat key: {}", main_stat_name))?;
+    let star = value["rarity"].as_u64().ok_or("missing rarity")? as u32;
+    let level = value["level"].as_u64().ok_or("missing level")? as u32;
+
+    let mut sub_stats: Vec<Option<ArtifactStat>> = vec![None, None, None, None];
+    if let Some(stats) = value["substats"].as_array() {
+        // GOOD allows empty substats with an empty key
+        let stats: Vec<&Value> = stats.iter().filter(|s| s["key"].as_str() != Some("")).collect();
+        if stats.len() > 4 {
+            return Err(format!("too many substats: {}", stats.len()));
+        }
+        for (i, stat) in stats.iter().enumerate() {
+            let name = stat["key"].as_str().ok_or("missing substat key")?;
+            let name = ArtifactStatName::from_good(name).ok_or(format!("unknown stat key: {}", name))?;
+            let v = stat["value"].as_f64().ok_or("missing substat value")?;
+            sub_stats[i] = Some(ArtifactStat {
+                value: StatValue::from_display(v, name.unit()),
+                name,
+            });
+        }
+    }
+    let mut sub_stats = sub_stats.into_iter();
+
+    let equip = match value["location"].as_str() {
+        None | Some("") => None,
+        Some(key) => Some(String::from(character_name(key).unwrap_or(key))),
+    };
+
+    Ok(InternalArtifact {
+        // GOOD only records the main stat key
+        main_stat: ArtifactStat {
+            value: main_stat_name.main_stat_value(star, level).ok_or("invalid main stat")?,
+            name: main_stat_name,
+        },
+        set_name,
+        slot,
+        star,
+        level,
+        sub_stat_1: sub_stats.next().unwrap(),
+        sub_stat_2: sub_stats.next().unwrap(),
+        sub_stat_3: sub_stats.next().unwrap(),
+        sub_stat_4: sub_stats.next().unwrap(),
+        equip,
+    })
+}
+
+impl<'a> GOODFormat<'a> {
+    pub fn new(results: &'a [InternalArtifact]) -> GOODFormat<'a> {
+        let artifacts: Vec<GOODArtifact<'a>> = results
+            .iter()
+            .filter(|artifact| unsupported(artifact).is_none())
+            .map(|artifact| GOODArtifact { artifact })
+            .collect();
+        GOODFormat {
+            artifacts: Some(artifacts),
+            weapons: None,
+            characters: None,
+            materials: None,
+        }
+    }
+
+    pub fn from_weapons(weapons: &'a [InternalWeapon]) -> GOODFormat<'a> {
+        let weapons: Vec<GOODWeapon<'a>> = weapons
+            .iter()
+            .filter(|weapon| match weapon.name {
+                WeaponName::Unknown { .. } => false,
+                _ => true,
+            })
+            .map(|weapon| GOODWeapon { weapon })
+            .collect();
+        GOODFormat {
+            artifacts: None,
+            weapons: Some(weapons),
+            characters: None,
+            materials: None,
+        }
+    }
+
+    pub fn from_characters(characters: &'a [InternalCharacter]) -> GOODFormat<'a> {
+        let characters: Vec<GOODCharacter<'a>> = characters
+            .iter()
+            .filter(|character| match character.name {
+                CharacterName::Unknown { .. } => false,
+                // GOOD keys the traveler by element, which is not recognized
+                CharacterName::Known("Traveler") => {
+                    warn!("旅行者的元素无法识别，GOOD格式中不包含旅行者");
+                    false
+                },
+                _ => true,
+            })
+            .map(|character| GOODCharacter { character })
+            .collect();
+        GOODFormat {
+            artifacts: None,
+            weapons: None,
+            characters: Some(characters),
+            materials: None,
+        }
+    }
+
+    pub fn from_materials(materials: &'a [InternalMaterial]) -> GOODFormat<'a> {
+        GOODFormat {
+            artifacts: None,
+            weapons: None,
+            characters: None,
+            materials: Some(GOODMaterials { materials }),
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Vec<InternalArtifact>, String> {
+        let json: Value = serde_json::from_str(s).map_err(|e| e.to_string())?;
+        if json["format"].as_str() != Some("GOOD") {
+            return Err(String::from("not a GOOD format file"));
+        }
+
+        let mut results: Vec<InternalArtifact> = Vec::new();
+        if let Some(arts) = json["artifacts"].as_array() {
+            for art in arts.iter() {
+                results.push(artifact_from_good(art)?);
+            }
+        }
+
+        Ok(results)
+    }
+
+    pub fn load(path: String) -> Result<Vec<InternalArtifact>, String> {
+        let content = match fs::read_to_string(&path) {
+            Err(why) => return Err(format!("couldn't read {}: {}", path, why)),
+            Ok(s) => s,
+        };
+        GOODFormat::parse(&content)
+    }
+}
+
+pub struct GOODExporter;
+
+impl Exporter for GOODExporter {
+    fn name(&self) -> &'static str {
+        "good"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn write(&self, results: &[InternalArtifact], w: &mut dyn Write) -> Result<(), String> {
+        serde_json::to_writer(w, &GOODFormat::new(results)).map_err(json_error)
+    }
+
+    fn unsupported(&self, artifact: &InternalArtifact) -> Option<String> {
+        unsupported(artifact)
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::artifact::internal_artifact::{ArtifactSetName, ArtifactSlot, InternalArtifact};
+use crate::expo::exporter::{io_error, Exporter};
+use crate::expo::yas::YasScanMetadata;
+use crate::scanner::yas_scanner::YasArtifactRecord;
+
+// fields below this OCR confidence are worth a second look
+const LOW_CONFIDENCE: f32 = 0.9;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 20px; background: #f4f4f4; }
+h2 { margin-top: 32px; }
+table { border-collapse: collapse; background: #fff; margin-bottom: 16px; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; vertical-align: top; }
+th { cursor: pointer; background: #eee; user-select: none; }
+img { width: 180px; cursor: zoom-in; }
+img.zoom { width: auto; }
+.warn { color: #c00; }
+.low { color: #c00; font-weight: bold; }
+ul { margin: 0; padding-left: 16px; }
+";
+
+// sort rows of a table by the data-sort attribute of the clicked column, click again to reverse
+const SCRIPT: &str = "
+document.querySelectorAll('th[data-col]').forEach(function (th) {
+  th.addEventListener('click', function () {
+    var tbody = th.closest('table').querySelector('tbody');
+    var col = parseInt(th.dataset.col);
+    var asc = th.dataset.asc !== 'true';
+    th.dataset.asc = asc;
+    var key = function (row) {
+      var v = row.children[col].dataset.sort;
+      var n = parseFloat(v);
+      return isNaN(n) ? v : n;
+    };
+    Array.from(tbody.rows).sort(function (a, b) {
+      var x = key(a), y = key(b);
+      var r = x < y ? -1 : x > y ? 1 : 0;
+      return asc ? r : -r;
+    }).forEach(function (row) { tbody.appendChild(row); });
+  });
+});
+document.querySelectorAll('img').forEach(function (img) {
+  img.addEventListener('click', function () { img.classList.toggle('zoom'); });
+});
+document.querySelectorAll('[data-time]').forEach(function (e) {
+  e.textContent = new Date(parseInt(e.dataset.time)).toLocaleString();
+});
+";
+
+const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut ret = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                ret.push(BASE64_CHARS[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                ret.push('=');
+            }
+        }
+    }
+    ret
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn slot_order(slot: &ArtifactSlot) -> u32 {
+    match slot {
+        ArtifactSlot::Flower => 0,
+        ArtifactSlot::Feather => 1,
+        ArtifactSlot::Sand => 2,
+        ArtifactSlot::Goblet => 3,
+        ArtifactSlot::Head => 4,
+    }
+}
+
+fn set_title(set_name: &ArtifactSetName) -> String {
+    match set_name {
+        ArtifactSetName::Unknown { raw_title } => format!("Unknown ({})", raw_title),
+        name => String::from(name.to_yas()),
+    }
+}
+
+struct ReportEntry<'a> {
+    artifact: &'a InternalArtifact,
+    record: Option<&'a YasArtifactRecord>,
+}
+
+fn render_row(entry: &ReportEntry) -> String {
+    let artifact = entry.artifact;
+    let mut row = String::from("<tr>");
+
+    match entry.record.and_then(|r| r.panel.as_ref()) {
+        Some(panel) => row += &format!("<td data-sort=\"\"><img src=\"data:image/jpeg;base64,{}\"></td>", base64(panel)),
+        None => row += "<td data-sort=\"\"></td>",
+    }
+    let index = entry.record.map(|r| r.meta.index.to_string()).unwrap_or_default();
+    row += &format!("<td data-sort=\"{0}\">{0}</td>", index);
+    row += &format!("<td data-sort=\"{0}\">{0}</td>", artifact.star);
+    row += &format!("<td data-sort=\"{0}\">+{0}</td>", artifact.level);
+    row += &format!(
+        "<td data-sort=\"{:?}\">{:?} {}</td>",
+        artifact.main_stat.name, artifact.main_stat.name, artifact.main_stat.value
+    );
+
+    let subs = artifact.sub_stats().iter()
+        .map(|s| format!("<li>{:?} +{}</li>", s.name, s.value))
+        .collect::<String>();
+    row += &format!("<td data-sort=\"{}\"><ul>{}</ul></td>", artifact.sub_stats().len(), subs);
+
+    let equip = escape(artifact.equip.as_deref().unwrap_or(""));
+    row += &format!("<td data-sort=\"{0}\">{0}</td>", equip);
+
+    match entry.record {
+        Some(record) => {
+            let confidence = record.meta.confidence;
+            let class = if confidence < LOW_CONFIDENCE { " class=\"low\"" } else { "" };
+            row += &format!("<td data-sort=\"{0:.3}\"{1}>{0:.3}</td>", confidence, class);
+        }
+        None => row += "<td data-sort=\"\"></td>",
+    }
+
+    let warnings = artifact.validate();
+    let items = warnings.iter()
+        .map(|w| format!("<li>{}</li>", escape(w)))
+        .collect::<String>();
+    row += &format!("<td data-sort=\"{}\" class=\"warn\"><ul>{}</ul></td>", warnings.len(), items);
+
+    row += "</tr>\n";
+    row
+}
+
+fn render(entries: &[ReportEntry], metadata: Option<&YasScanMetadata>) -> String {
+    let mut groups: BTreeMap<(String, u32), Vec<&ReportEntry>> = BTreeMap::new();
+    for entry in entries.iter() {
+        let key = (set_title(&entry.artifact.set_name), slot_order(&entry.artifact.slot));
+        groups.entry(key).or_insert_with(Vec::new).push(entry);
+    }
+    let warning_count = entries.iter().filter(|e| !e.artifact.validate().is_empty()).count();
+
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Yas</title>\n");
+    html += &format!("<style>{}</style>\n</head>\n<body>\n<h1>Yas</h1>\n", STYLE);
+    if let Some(meta) = metadata {
+        html += &format!(
+            "<p>Yas {} · <span data-time=\"{}\"></span> · {}x{} · {:.1}s</p>\n",
+            escape(&meta.yas_version), meta.time, meta.width, meta.height, meta.duration
+        );
+    }
+    html += &format!("<p>圣遗物：{}，有警告：{}</p>\n", entries.len(), warning_count);
+
+    for ((set_name, _), group) in groups.iter() {
+        html += &format!("<h2>{} · {:?} ({})</h2>\n", escape(set_name), group[0].artifact.slot, group.len());
+        html += "<table>\n<thead><tr>";
+        let headers = ["截图", "序号", "星级", "等级", "主词条", "副词条", "装备", "置信度", "警告"];
+        for (i, h) in headers.iter().enumerate() {
+            html += &format!("<th data-col=\"{}\">{}</th>", i, h);
+        }
+        html += "</tr></thead>\n<tbody>\n";
+        for entry in group.iter() {
+            html += &render_row(entry);
+        }
+        html += "</tbody>\n</table>\n";
+    }
+
+    html += &format!("<script>{}</script>\n</body>\n</html>\n", SCRIPT);
+    html
+}
+
+// Offline report of a scan, artifacts grouped by set and slot, with panel crops when available
+pub struct HtmlExporter;
+
+impl Exporter for HtmlExporter {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn file_name(&self) -> String {
+        String::from("artifacts.html")
+    }
+
+    fn write(&self, results: &[InternalArtifact], w: &mut dyn Write) -> Result<(), String> {
+        let entries = results.iter()
+            .map(|artifact| ReportEntry { artifact, record: None })
+            .collect::<Vec<_>>();
+        w.write_all(render(&entries, None).as_bytes()).map_err(io_error)
+    }
+
+    fn write_scan(
+        &self,
+        records: &[YasArtifactRecord],
+        metadata: &YasScanMetadata,
+        w: &mut dyn Write,
+    ) -> Result<(), String> {
+        let entries = records.iter()
+            .map(|record| ReportEntry { artifact: &record.artifact, record: Some(record) })
+            .collect::<Vec<_>>();
+        w.write_all(render(&entries, Some(metadata)).as_bytes()).map_err(io_error)
+    }
+}
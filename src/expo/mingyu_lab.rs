@@ -1,9 +1,11 @@
 use crate::artifact::internal_artifact::{
-    ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName, InternalArtifact,
+    ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName, InternalArtifact, StatValue,
 };
-use serde::ser::{Serialize, SerializeMap, Serializer};
-use std::fs::File;
-use std::io::prelude::*;
+use crate::expo::exporter::{json_error, Exporter};
+use serde::ser::{Error, Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
 
 struct MingyuLabArtifact<'a> {
     artifact: &'a InternalArtifact,
@@ -21,18 +23,14 @@ impl<'a> Serialize for MingyuLabArtifact<'a> {
 
         let extract_stat_value = |maybe_stat: &Option<ArtifactStat>| match maybe_stat {
             None => 0.0,
-            Some(stat) => match stat.name {
-                ArtifactStatName::Atk
-                | ArtifactStatName::ElementalMastery
-                | ArtifactStatName::Hp
-                | ArtifactStatName::Def => stat.value,
-                _ => stat.value * 100.0,
-            },
+            Some(stat) => stat.value.display_value(),
         };
 
         let artifact = &self.artifact;
         let mut root = serializer.serialize_map(Some(13))?;
-        root.serialize_entry("asKey", artifact.set_name.to_mingyu_lab())?;
+        let set_key = artifact.set_name.to_mingyu_lab()
+            .ok_or_else(|| S::Error::custom(format!("set not supported by Mingyulab: {:?}", artifact.set_name)))?;
+        root.serialize_entry("asKey", set_key)?;
         root.serialize_entry("rarity", &artifact.star)?;
         root.serialize_entry("slot", artifact.slot.to_mingyu_lab())?;
         root.serialize_entry("level", &artifact.level)?;
@@ -66,12 +64,36 @@ impl ArtifactStatName {
             ArtifactStatName::ElectroBonus => "electroDamage",
             ArtifactStatName::PyroBonus => "pyroDamage",
             ArtifactStatName::HydroBonus => "hydroDamage",
-            ArtifactStatName::CryoBonus => "pyroDamage",
+            ArtifactStatName::CryoBonus => "cryoDamage",
             ArtifactStatName::AnemoBonus => "anemoDamage",
             ArtifactStatName::GeoBonus => "geoDamage",
             ArtifactStatName::PhysicalBonus => "physicalDamage",
         }
     }
+
+    pub fn from_mingyu_lab(s: &str) -> Option<ArtifactStatName> {
+        match s {
+            "healing" => Some(ArtifactStatName::HealingBonus),
+            "critDamage" => Some(ArtifactStatName::CriticalDamage),
+            "critRate" => Some(ArtifactStatName::Critical),
+            "flatATK" => Some(ArtifactStatName::Atk),
+            "percentATK" => Some(ArtifactStatName::AtkPercentage),
+            "elementalMastery" => Some(ArtifactStatName::ElementalMastery),
+            "energyRecharge" => Some(ArtifactStatName::Recharge),
+            "percentHP" => Some(ArtifactStatName::HpPercentage),
+            "flatHP" => Some(ArtifactStatName::Hp),
+            "percentDEF" => Some(ArtifactStatName::DefPercentage),
+            "flatDEF" => Some(ArtifactStatName::Def),
+            "electroDamage" => Some(ArtifactStatName::ElectroBonus),
+            "pyroDamage" => Some(ArtifactStatName::PyroBonus),
+            "hydroDamage" => Some(ArtifactStatName::HydroBonus),
+            "cryoDamage" => Some(ArtifactStatName::CryoBonus),
+            "anemoDamage" => Some(ArtifactStatName::AnemoBonus),
+            "geoDamage" => Some(ArtifactStatName::GeoBonus),
+            "physicalDamage" => Some(ArtifactStatName::PhysicalBonus),
+            _ => None,
+        }
+    }
 }
 
 impl ArtifactSlot {
@@ -84,52 +106,158 @@ impl ArtifactSlot {
             ArtifactSlot::Head => "circlet",
         }
     }
+
+    pub fn from_mingyu_lab(s: &str) -> Option<ArtifactSlot> {
+        match s {
+            "flower" => Some(ArtifactSlot::Flower),
+            "plume" => Some(ArtifactSlot::Feather),
+            "eon" => Some(ArtifactSlot::Sand),
+            "goblet" => Some(ArtifactSlot::Goblet),
+            "circlet" => Some(ArtifactSlot::Head),
+            _ => None,
+        }
+    }
 }
 
 impl ArtifactSetName {
-    pub fn to_mingyu_lab(&self) -> &'static str {
+    pub fn to_mingyu_lab(&self) -> Option<&'static str> {
         match self {
-            ArtifactSetName::ArchaicPetra => "archaic_petra",
-            ArtifactSetName::HeartOfDepth => "heart_of_depth",
-            ArtifactSetName::BlizzardStrayer => "blizzard_walker",
-            ArtifactSetName::RetracingBolide => "retracing_bolide",
-            ArtifactSetName::NoblesseOblige => "noblesse_oblige",
-            ArtifactSetName::GladiatorFinale => "gladiators_finale",
-            ArtifactSetName::MaidenBeloved => "maiden_beloved",
-            ArtifactSetName::ViridescentVenerer => "viridescent_venerer",
-            ArtifactSetName::LavaWalker => "lavawalker",
-            ArtifactSetName::CrimsonWitch => "crimson_witch_of_flames",
-            ArtifactSetName::ThunderSmoother => "thundersoother",
-            ArtifactSetName::ThunderingFury => "thundering_fury",
-            ArtifactSetName::BloodstainedChivalry => "bloodstained_chivalry",
-            ArtifactSetName::WandererTroupe => "wanderers_troupe",
-            ArtifactSetName::Scholar => "scholar",
-            ArtifactSetName::Gambler => "gambler",
-            ArtifactSetName::TinyMiracle => "tiny_miracle",
-            ArtifactSetName::MartialArtist => "martial_artist",
-            ArtifactSetName::BraveHeart => "brave_heart",
-            ArtifactSetName::ResolutionOfSojourner => "resolution_of_sojourner",
-            ArtifactSetName::DefenderWill => "defenders_will",
-            ArtifactSetName::Berserker => "berserker",
-            ArtifactSetName::Instructor => "instructor",
-            ArtifactSetName::Exile => "the_exile",
-            ArtifactSetName::PrayersForWisdom => "prayers_of_wisdom",
-            ArtifactSetName::PrayersToSpringtime => "prayers_of_springtime",
-            ArtifactSetName::PrayersForIllumination => "prayers_of_illumination",
-            ArtifactSetName::PrayersForDestiny => "prayers_of_destiny",
-            ArtifactSetName::PaleFlame => "pale_flame",
-            ArtifactSetName::TenacityOfTheMillelith => "tenacity_of_the_millelith",
-            ArtifactSetName::EmblemOfSeveredFate => "seal_of_insulation",
-            ArtifactSetName::ShimenawaReminiscence => "reminiscence_of_shime",
-            ArtifactSetName::HuskOfOpulentDreams => "husk_of_opulent_dreams",
-            ArtifactSetName::OceanHuedClam => "divine_chorus",
+            ArtifactSetName::ArchaicPetra => Some("archaic_petra"),
+            ArtifactSetName::HeartOfDepth => Some("heart_of_depth"),
+            ArtifactSetName::BlizzardStrayer => Some("blizzard_walker"),
+            ArtifactSetName::RetracingBolide => Some("retracing_bolide"),
+            ArtifactSetName::NoblesseOblige => Some("noblesse_oblige"),
+            ArtifactSetName::GladiatorFinale => Some("gladiators_finale"),
+            ArtifactSetName::MaidenBeloved => Some("maiden_beloved"),
+            ArtifactSetName::ViridescentVenerer => Some("viridescent_venerer"),
+            ArtifactSetName::LavaWalker => Some("lavawalker"),
+            ArtifactSetName::CrimsonWitch => Some("crimson_witch_of_flames"),
+            ArtifactSetName::ThunderSmoother => Some("thundersoother"),
+            ArtifactSetName::ThunderingFury => Some("thundering_fury"),
+            ArtifactSetName::BloodstainedChivalry => Some("bloodstained_chivalry"),
+            ArtifactSetName::WandererTroupe => Some("wanderers_troupe"),
+            ArtifactSetName::Scholar => Some("scholar"),
+            ArtifactSetName::Gambler => Some("gambler"),
+            ArtifactSetName::TinyMiracle => Some("tiny_miracle"),
+            ArtifactSetName::MartialArtist => Some("martial_artist"),
+            ArtifactSetName::BraveHeart => Some("brave_heart"),
+            ArtifactSetName::ResolutionOfSojourner => Some("resolution_of_sojourner"),
+            ArtifactSetName::DefenderWill => Some("defenders_will"),
+            ArtifactSetName::Berserker => Some("berserker"),
+            ArtifactSetName::Instructor => Some("instructor"),
+            ArtifactSetName::Exile => Some("the_exile"),
+            ArtifactSetName::PrayersForWisdom => Some("prayers_of_wisdom"),
+            ArtifactSetName::PrayersToSpringtime => Some("prayers_of_springtime"),
+            ArtifactSetName::PrayersForIllumination => Some("prayers_of_illumination"),
+            ArtifactSetName::PrayersForDestiny => Some("prayers_of_destiny"),
+            ArtifactSetName::PaleFlame => Some("pale_flame"),
+            ArtifactSetName::TenacityOfTheMillelith => Some("tenacity_of_the_millelith"),
+            ArtifactSetName::EmblemOfSeveredFate => Some("seal_of_insulation"),
+            ArtifactSetName::ShimenawaReminiscence => Some("reminiscence_of_shime"),
+            ArtifactSetName::HuskOfOpulentDreams => Some("husk_of_opulent_dreams"),
+            ArtifactSetName::OceanHuedClam => Some("divine_chorus"),
 
             // Not supported by Mingyulab
-            ArtifactSetName::Adventurer => unreachable!(),
-            ArtifactSetName::LuckyDog => unreachable!(),
-            ArtifactSetName::TravelingDoctor => unreachable!(),
+            ArtifactSetName::Adventurer => None,
+            ArtifactSetName::LuckyDog => None,
+            ArtifactSetName::TravelingDoctor => None,
+            ArtifactSetName::Unknown { .. } => None,
         }
     }
+
+    pub fn from_mingyu_lab(s: &str) -> Option<ArtifactSetName> {
+        match s {
+            "archaic_petra" => Some(ArtifactSetName::ArchaicPetra),
+            "heart_of_depth" => Some(ArtifactSetName::HeartOfDepth),
+            "blizzard_walker" => Some(ArtifactSetName::BlizzardStrayer),
+            "retracing_bolide" => Some(ArtifactSetName::RetracingBolide),
+            "noblesse_oblige" => Some(ArtifactSetName::NoblesseOblige),
+            "gladiators_finale" => Some(ArtifactSetName::GladiatorFinale),
+            "maiden_beloved" => Some(ArtifactSetName::MaidenBeloved),
+            "viridescent_venerer" => Some(ArtifactSetName::ViridescentVenerer),
+            "lavawalker" => Some(ArtifactSetName::LavaWalker),
+            "crimson_witch_of_flames" => Some(ArtifactSetName::CrimsonWitch),
+            "thundersoother" => Some(ArtifactSetName::ThunderSmoother),
+            "thundering_fury" => Some(ArtifactSetName::ThunderingFury),
+            "bloodstained_chivalry" => Some(ArtifactSetName::BloodstainedChivalry),
+            "wanderers_troupe" => Some(ArtifactSetName::WandererTroupe),
+            "scholar" => Some(ArtifactSetName::Scholar),
+            "gambler" => Some(ArtifactSetName::Gambler),
+            "tiny_miracle" => Some(ArtifactSetName::TinyMiracle),
+            "martial_artist" => Some(ArtifactSetName::MartialArtist),
+            "brave_heart" => Some(ArtifactSetName::BraveHeart),
+            "resolution_of_sojourner" => Some(ArtifactSetName::ResolutionOfSojourner),
+            "defenders_will" => Some(ArtifactSetName::DefenderWill),
+            "berserker" => Some(ArtifactSetName::Berserker),
+            "instructor" => Some(ArtifactSetName::Instructor),
+            "the_exile" => Some(ArtifactSetName::Exile),
+            "prayers_of_wisdom" => Some(ArtifactSetName::PrayersForWisdom),
+            "prayers_of_springtime" => Some(ArtifactSetName::PrayersToSpringtime),
+            "prayers_of_illumination" => Some(ArtifactSetName::PrayersForIllumination),
+            "prayers_of_destiny" => Some(ArtifactSetName::PrayersForDestiny),
+            "pale_flame" => Some(ArtifactSetName::PaleFlame),
+            "tenacity_of_the_millelith" => Some(ArtifactSetName::TenacityOfTheMillelith),
+            "seal_of_insulation" => Some(ArtifactSetName::EmblemOfSeveredFate),
+            "reminiscence_of_shime" => Some(ArtifactSetName::ShimenawaReminiscence),
+            "husk_of_opulent_dreams" => Some(ArtifactSetName::HuskOfOpulentDreams),
+            "divine_chorus" => Some(ArtifactSetName::OceanHuedClam),
+            _ => None,
+        }
+    }
+}
+
+fn stat_from_mingyu_lab(value: &Value, type_key: &str, value_key: &str) -> Result<Option<ArtifactStat>, String> {
+    let name = value[type_key].as_str().ok_or(format!("missing {}", type_key))?;
+    let name = ArtifactStatName::from_mingyu_lab(name).ok_or(format!("unknown stat name: {}", name))?;
+    let v = value[value_key].as_f64().ok_or(format!("missing {}", value_key))?;
+    // empty sub stats are written as flatATK with value 0
+    if v == 0.0 {
+        return Ok(None);
+    }
+
+    Ok(Some(ArtifactStat {
+        value: StatValue::from_display(v, name.unit()),
+        name,
+    }))
+}
+
+fn artifact_from_mingyu_lab(value: &Value) -> Result<InternalArtifact, String> {
+    let set_name = value["asKey"].as_str().ok_or("missing asKey")?;
+    let set_name = ArtifactSetName::from_mingyu_lab(set_name).ok_or(format!("unknown set name: {}", set_name))?;
+    let slot = value["slot"].as_str().ok_or("missing slot")?;
+    let slot = ArtifactSlot::from_mingyu_lab(slot).ok_or(format!("unknown slot: {}", slot))?;
+    let main_stat_name = value["mainStat"].as_str().ok_or("missing mainStat")?;
+    let main_stat_name = ArtifactStatName::from_mingyu_lab(main_stat_name)
+        .ok_or(format!("unknown stat name: {}", main_stat_name))?;
+    let star = value["rarity"].as_u64().ok_or("missing rarity")? as u32;
+    let level = value["level"].as_u64().ok_or("missing level")? as u32;
+
+    Ok(InternalArtifact {
+        // mingyulab only records the main stat name
+        main_stat: ArtifactStat {
+            value: main_stat_name.main_stat_value(star, level).ok_or("invalid main stat")?,
+            name: main_stat_name,
+        },
+        set_name,
+        slot,
+        star,
+        level,
+        sub_stat_1: stat_from_mingyu_lab(value, "subStat1Type", "subStat1Value")?,
+        sub_stat_2: stat_from_mingyu_lab(value, "subStat2Type", "subStat2Value")?,
+        sub_stat_3: stat_from_mingyu_lab(value, "subStat3Type", "subStat3Value")?,
+        sub_stat_4: stat_from_mingyu_lab(value, "subStat4Type", "subStat4Value")?,
+        equip: None,
+    })
+}
+
+fn unsupported(artifact: &InternalArtifact) -> Option<String> {
+    match artifact.set_name {
+        ArtifactSetName::Adventurer
+        | ArtifactSetName::LuckyDog
+        | ArtifactSetName::TravelingDoctor => Some(String::from("原魔计算器不支持该套装")),
+        ArtifactSetName::Unknown { .. } => Some(String::from("未知套装")),
+        _ => None,
+    }
 }
 
 pub struct MingyuLabFormat<'a> {
@@ -137,28 +265,97 @@ pub struct MingyuLabFormat<'a> {
 }
 
 impl<'a> MingyuLabFormat<'a> {
-    pub fn new(results: &'a Vec<InternalArtifact>) -> MingyuLabFormat {
+    pub fn new(results: &'a [InternalArtifact]) -> MingyuLabFormat<'a> {
         let artifacts: Vec<MingyuLabArtifact<'a>> = results
             .into_iter()
-            .filter(|artifact| {
-                artifact.set_name != ArtifactSetName::Adventurer
-                    && artifact.set_name != ArtifactSetName::LuckyDog
-                    && artifact.set_name != ArtifactSetName::TravelingDoctor
-            })
+            .filter(|artifact| unsupported(artifact).is_none())
             .map(|artifact| MingyuLabArtifact { artifact })
             .collect();
         MingyuLabFormat { artifacts }
     }
 
-    pub fn save(&self, path: String) {
-        let mut file = match File::create(&path) {
-            Err(why) => panic!("couldn't create {}: {}", path, why),
-            Ok(file) => file,
+    pub fn parse(s: &str) -> Result<Vec<InternalArtifact>, String> {
+        let json: Value = serde_json::from_str(s).map_err(|e| e.to_string())?;
+        let arts = match json.as_array() {
+            Some(v) => v,
+            None => return Err(String::from("mingyulab format should be an array")),
         };
-        let s = serde_json::to_string(&self.artifacts).unwrap();
-        match file.write_all(s.as_bytes()) {
-            Err(why) => panic!("couldn't write to {}: {}", path, why),
-            _ => {}
+
+        let mut results: Vec<InternalArtifact> = Vec::new();
+        for art in arts.iter() {
+            results.push(artifact_from_mingyu_lab(art)?);
         }
+
+        Ok(results)
+    }
+
+    pub fn load(path: String) -> Result<Vec<InternalArtifact>, String> {
+        let content = match fs::read_to_string(&path) {
+            Err(why) => return Err(format!("couldn't read {}: {}", path, why)),
+            Ok(s) => s,
+        };
+        MingyuLabFormat::parse(&content)
+    }
+}
+
+pub struct MingyuLabExporter;
+
+impl Exporter for MingyuLabExporter {
+    fn name(&self) -> &'static str {
+        "mingyulab"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn write(&self, results: &[InternalArtifact], w: &mut dyn Write) -> Result<(), String> {
+        serde_json::to_writer(w, &MingyuLabFormat::new(results).artifacts).map_err(json_error)
+    }
+
+    fn unsupported(&self, artifact: &InternalArtifact) -> Option<String> {
+        unsupported(artifact)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"[
+        {
+            "asKey": "seal_of_insulation", "rarity": 5, "slot": "flower", "level": 20, "mainStat": "flatHP",
+            "subStat1Type": "critRate", "subStat1Value": 10.5, "subStat2Type": "critDamage", "subStat2Value": 21.8,
+            "subStat3Type": "energyRecharge", "subStat3Value": 5.8, "subStat4Type": "flatATK", "subStat4Value": 19
+        },
+        {
+            "asKey": "gladiators_finale", "rarity": 5, "slot": "goblet", "level": 2, "mainStat": "cryoDamage",
+            "subStat1Type": "elementalMastery", "subStat1Value": 23, "subStat2Type": "percentDEF", "subStat2Value": 7.3,
+            "subStat3Type": "flatATK", "subStat3Value": 0, "subStat4Type": "flatATK", "subStat4Value": 0
+        },
+        {
+            "asKey": "seal_of_insulation", "rarity": 4, "slot": "circlet", "level": 4, "mainStat": "critRate",
+            "subStat1Type": "percentHP", "subStat1Value": 4.7, "subStat2Type": "flatATK", "subStat2Value": 0,
+            "subStat3Type": "flatATK", "subStat3Value": 0, "subStat4Type": "flatATK", "subStat4Value": 0
+        }
+    ]"#;
+
+    #[test]
+    fn parse_fills_main_stat_values() {
+        let artifacts = MingyuLabFormat::parse(SAMPLE).unwrap();
+        assert_eq!(artifacts.len(), 3);
+        assert_eq!(artifacts[0].main_stat.value.to_string(), "4780");
+        assert_eq!(artifacts[1].main_stat.value.to_string(), "11.0%");
+        assert_eq!(artifacts[2].main_stat.value.to_string(), "9.0%");
+        assert_eq!(artifacts[1].sub_stats().len(), 2);
+    }
+
+    #[test]
+    fn round_trip() {
+        let artifacts = MingyuLabFormat::parse(SAMPLE).unwrap();
+        let mut buf: Vec<u8> = Vec::new();
+        MingyuLabExporter.write(&artifacts, &mut buf).unwrap();
+        let parsed = MingyuLabFormat::parse(&String::from_utf8(buf).unwrap()).unwrap();
+        assert_eq!(parsed, artifacts);
     }
 }
@@ -1,2 +1,69 @@
+use std::fs;
+
+use serde_json::Value;
+
+use crate::artifact::internal_artifact::InternalArtifact;
+use crate::expo::good::GOODFormat;
+use crate::expo::mingyu_lab::MingyuLabFormat;
+use crate::expo::mona_uranai::MonaFormat;
+use crate::expo::yas::YasFormat;
+
 pub mod mona_uranai;
-pub mod mingyu_lab;
\ No newline at end of file
+pub mod mingyu_lab;
+pub mod yas;
+pub mod good;
+pub mod csv;
+pub mod exporter;
+pub mod stream;
+pub mod html;
+pub mod mona_merge;
+
+// formats that can be read back
+pub const READ_FORMATS: [&str; 4] = ["mona", "mingyulab", "yas", "good"];
+
+// formats which only record the name of the main stat, their readers take the value from the main stat table
+pub const NO_MAIN_STAT_VALUE_FORMATS: [&str; 2] = ["mingyulab", "good"];
+
+// Guess the format of a previously exported file from its content
+pub fn detect_format(content: &str) -> Option<&'static str> {
+    let json: Value = serde_json::from_str(content).ok()?;
+    if json.get("format").and_then(|f| f.as_str()) == Some("yas") {
+        Some("yas")
+    } else if json.get("format").and_then(|f| f.as_str()) == Some("GOOD") {
+        Some("good")
+    } else if json.is_array() {
+        Some("mingyulab")
+    } else if json.get("flower").is_some() || json.get("version").is_some() {
+        Some("mona")
+    } else {
+        None
+    }
+}
+
+pub fn parse_artifacts(content: &str, format: &str) -> Result<Vec<InternalArtifact>, String> {
+    match format {
+        "mona" => MonaFormat::parse(content),
+        "mingyulab" => MingyuLabFormat::parse(content),
+        "yas" => YasFormat::parse(content),
+        "good" => GOODFormat::parse(content),
+        _ => Err(format!("unsupported format: {}", format)),
+    }
+}
+
+// Load an exported file, `format` is detected from the content if not given
+pub fn load_artifacts(path: &str, format: Option<&str>) -> Result<Vec<InternalArtifact>, String> {
+    load_artifacts_with_format(path, format).map(|(artifacts, _)| artifacts)
+}
+
+// Like `load_artifacts`, also returns the format the file was read as
+pub fn load_artifacts_with_format(path: &str, format: Option<&str>) -> Result<(Vec<InternalArtifact>, String), String> {
+    let content = match fs::read_to_string(path) {
+        Err(why) => return Err(format!("couldn't read {}: {}", path, why)),
+        Ok(s) => s,
+    };
+    let format = match format {
+        Some(f) => f,
+        None => detect_format(&content).ok_or(format!("unknown format: {}", path))?,
+    };
+    Ok((parse_artifacts(&content, format)?, String::from(format)))
+}
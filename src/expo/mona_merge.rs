@@ -0,0 +1,134 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::artifact::diff::{pair_artifacts, ArtifactDiff};
+use crate::artifact::internal_artifact::{ArtifactSetName, InternalArtifact};
+use crate::expo::mona_uranai::MonaEntry;
+use crate::scanner::yas_scanner::ScanCoverage;
+
+const SLOTS: [&str; 5] = ["flower", "feather", "sand", "cup", "head"];
+
+// Flags set by the user in mona that the scan cannot know about,
+// they win over the scanned value. Unknown fields are always kept
+const USER_FIELDS: [&str; 2] = ["omit", "lock"];
+
+// Result of merging a scan into an existing mona file
+pub struct MonaMerge {
+    pub merged: Value,
+    pub diff: ArtifactDiff,
+    // number of artifacts whose fields were carried over from the old file
+    pub kept: usize,
+    // number of those with `omit` set
+    pub omitted: usize,
+    // old artifacts the scan did not go through, they are kept as they are
+    pub outside: usize,
+}
+
+impl MonaMerge {
+    // `version` is one of MONA_FORMAT_VERSIONS.
+    // Only old artifacts within `coverage` can be found gone, the rest stay unless the scan found them
+    pub fn new(
+        old_content: &str,
+        results: &[InternalArtifact],
+        coverage: &ScanCoverage,
+        version: &str,
+    ) -> Result<MonaMerge, String> {
+        let old_json: Value = serde_json::from_str(old_content).map_err(|e| e.to_string())?;
+        let old_root = match old_json.as_object() {
+            Some(v) => v,
+            None => return Err(String::from("mona format should be an object")),
+        };
+
+        let mut old_values: Vec<&Value> = Vec::new();
+        let mut old: Vec<InternalArtifact> = Vec::new();
+        for key in SLOTS.iter() {
+            if let Some(arts) = old_root.get(*key).and_then(|v| v.as_array()) {
+                for art in arts.iter() {
+                    old.push(InternalArtifact::from_mona(art)?);
+                    old_values.push(art);
+                }
+            }
+        }
+
+        // mona cannot hold sets it does not know
+        let new = results.iter()
+            .filter(|a| match a.set_name {
+                ArtifactSetName::Unknown { .. } => false,
+                _ => true,
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let pairs = pair_artifacts(&old, &new);
+        let mut previous: Vec<Option<&Value>> = vec![None; new.len()];
+        for (i, pair) in pairs.iter().enumerate() {
+            if let Some(j) = *pair {
+                previous[j] = Some(old_values[i]);
+            }
+        }
+        // unmatched old artifacts the scan would have seen are gone
+        let mut in_range: Vec<InternalArtifact> = Vec::new();
+        let mut in_range_pairs: Vec<Option<usize>> = Vec::new();
+        let mut outside: Vec<usize> = Vec::new();
+        for (i, pair) in pairs.iter().enumerate() {
+            if pair.is_none() && !coverage.contains(&old[i]) {
+                outside.push(i);
+            } else {
+                in_range.push(old[i].clone());
+                in_range_pairs.push(*pair);
+            }
+        }
+
+        let mut root = old_root.clone();
+        for key in SLOTS.iter() {
+            root.insert(String::from(*key), Value::Array(Vec::new()));
+        }
+        root.insert(String::from("version"), Value::String(String::from(version)));
+
+        let mut kept = 0;
+        let mut omitted = 0;
+        for (art, prev) in new.iter().zip(previous.iter()) {
+            let mut value = serde_json::to_value(&MonaEntry::new(art, version)).map_err(|e| e.to_string())?;
+            if let (Some(prev), Some(obj)) = (prev.and_then(|p| p.as_object()), value.as_object_mut()) {
+                merge_fields(obj, prev);
+                kept += 1;
+                if obj.get("omit").and_then(|v| v.as_bool()) == Some(true) {
+                    omitted += 1;
+                }
+            }
+
+            if let Some(Value::Array(list)) = root.get_mut(&art.slot.to_mona()) {
+                list.push(value);
+            }
+        }
+        for &i in outside.iter() {
+            if let Some(Value::Array(list)) = root.get_mut(&old[i].slot.to_mona()) {
+                list.push(old_values[i].clone());
+            }
+        }
+
+        Ok(MonaMerge {
+            merged: Value::Object(root),
+            diff: ArtifactDiff::from_pairs(&in_range, &new, &in_range_pairs),
+            kept,
+            omitted,
+            outside: outside.len(),
+        })
+    }
+}
+
+fn merge_fields(new: &mut Map<String, Value>, old: &Map<String, Value>) {
+    for (k, v) in old.iter() {
+        if USER_FIELDS.contains(&k.as_str()) || !new.contains_key(k) {
+            new.insert(k.clone(), v.clone());
+        }
+    }
+}
+
+impl fmt::Display for MonaMerge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "kept user fields: {}, omitted: {}, not scanned: {}", self.kept, self.omitted, self.outside)?;
+        write!(f, "{}", self.diff)
+    }
+}
@@ -1,10 +1,14 @@
 use std::convert::From;
-use std::fs::File;
-use std::io::prelude::*;
+use std::fs;
+use std::io::Write;
 
 use serde::ser::{Serialize, Serializer, SerializeMap};
+use serde_json::Value;
+use log::warn;
 
-use crate::artifact::internal_artifact::{ArtifactStatName, ArtifactSetName, ArtifactSlot, InternalArtifact, ArtifactStat};
+use crate::artifact::internal_artifact::{ArtifactStatName, ArtifactSetName, ArtifactSlot, InternalArtifact, ArtifactStat, StatValue};
+use crate::expo::exporter::{json_error, Exporter};
+use crate::character::internal_character::{character_key, character_name};
 
 
 type MonaArtifact = InternalArtifact;
@@ -33,11 +37,37 @@ impl ArtifactStatName {
         };
         String::from(temp)
     }
+
+    pub fn from_mona(s: &str) -> Option<ArtifactStatName> {
+        match s {
+            "cureEffect" => Some(ArtifactStatName::HealingBonus),
+            "criticalDamage" => Some(ArtifactStatName::CriticalDamage),
+            "critical" => Some(ArtifactStatName::Critical),
+            "attackStatic" => Some(ArtifactStatName::Atk),
+            "attackPercentage" => Some(ArtifactStatName::AtkPercentage),
+            "elementalMastery" => Some(ArtifactStatName::ElementalMastery),
+            "recharge" => Some(ArtifactStatName::Recharge),
+            "lifePercentage" => Some(ArtifactStatName::HpPercentage),
+            "lifeStatic" => Some(ArtifactStatName::Hp),
+            "defendPercentage" => Some(ArtifactStatName::DefPercentage),
+            "defendStatic" => Some(ArtifactStatName::Def),
+            "thunderBonus" => Some(ArtifactStatName::ElectroBonus),
+            "fireBonus" => Some(ArtifactStatName::PyroBonus),
+            "waterBonus" => Some(ArtifactStatName::HydroBonus),
+            "iceBonus" => Some(ArtifactStatName::CryoBonus),
+            "windBonus" => Some(ArtifactStatName::AnemoBonus),
+            "rockBonus" => Some(ArtifactStatName::GeoBonus),
+            "physicalBonus" => Some(ArtifactStatName::PhysicalBonus),
+            _ => None,
+        }
+    }
 }
 
 impl ArtifactSetName {
     pub fn to_mona(&self) -> String {
         let temp = match self {
+            // Not supported by Mona, only reachable when serializing a single artifact
+            ArtifactSetName::Unknown { raw_title } => return raw_title.clone(),
             ArtifactSetName::ArchaicPetra => "archaicPetra",
             ArtifactSetName::HeartOfDepth => "heartOfDepth",
             ArtifactSetName::BlizzardStrayer => "blizzardStrayer",
@@ -78,6 +108,49 @@ impl ArtifactSetName {
         };
         String::from(temp)
     }
+
+    pub fn from_mona(s: &str) -> Option<ArtifactSetName> {
+        match s {
+            "archaicPetra" => Some(ArtifactSetName::ArchaicPetra),
+            "heartOfDepth" => Some(ArtifactSetName::HeartOfDepth),
+            "blizzardStrayer" => Some(ArtifactSetName::BlizzardStrayer),
+            "retracingBolide" => Some(ArtifactSetName::RetracingBolide),
+            "noblesseOblige" => Some(ArtifactSetName::NoblesseOblige),
+            "gladiatorFinale" => Some(ArtifactSetName::GladiatorFinale),
+            "maidenBeloved" => Some(ArtifactSetName::MaidenBeloved),
+            "viridescentVenerer" => Some(ArtifactSetName::ViridescentVenerer),
+            "lavaWalker" => Some(ArtifactSetName::LavaWalker),
+            "crimsonWitch" => Some(ArtifactSetName::CrimsonWitch),
+            "thunderSmoother" => Some(ArtifactSetName::ThunderSmoother),
+            "thunderingFury" => Some(ArtifactSetName::ThunderingFury),
+            "bloodstainedChivalry" => Some(ArtifactSetName::BloodstainedChivalry),
+            "wandererTroupe" => Some(ArtifactSetName::WandererTroupe),
+            "scholar" => Some(ArtifactSetName::Scholar),
+            "gambler" => Some(ArtifactSetName::Gambler),
+            "tinyMiracle" => Some(ArtifactSetName::TinyMiracle),
+            "martialArtist" => Some(ArtifactSetName::MartialArtist),
+            "braveHeart" => Some(ArtifactSetName::BraveHeart),
+            "resolutionOfSojourner" => Some(ArtifactSetName::ResolutionOfSojourner),
+            "defenderWill" => Some(ArtifactSetName::DefenderWill),
+            "berserker" => Some(ArtifactSetName::Berserker),
+            "instructor" => Some(ArtifactSetName::Instructor),
+            "exile" => Some(ArtifactSetName::Exile),
+            "adventurer" => Some(ArtifactSetName::Adventurer),
+            "luckyDog" => Some(ArtifactSetName::LuckyDog),
+            "travelingDoctor" => Some(ArtifactSetName::TravelingDoctor),
+            "prayersForWisdom" => Some(ArtifactSetName::PrayersForWisdom),
+            "prayersToSpringtime" => Some(ArtifactSetName::PrayersToSpringtime),
+            "prayersForIllumination" => Some(ArtifactSetName::PrayersForIllumination),
+            "prayersForDestiny" => Some(ArtifactSetName::PrayersForDestiny),
+            "paleFlame" => Some(ArtifactSetName::PaleFlame),
+            "tenacityOfTheMillelith" => Some(ArtifactSetName::TenacityOfTheMillelith),
+            "emblemOfSeveredFate" => Some(ArtifactSetName::EmblemOfSeveredFate),
+            "shimenawaReminiscence" => Some(ArtifactSetName::ShimenawaReminiscence),
+            "huskOfOpulentDreams" => Some(ArtifactSetName::HuskOfOpulentDreams),
+            "oceanHuedClam" => Some(ArtifactSetName::OceanHuedClam),
+            _ => None,
+        }
+    }
 }
 
 impl ArtifactSlot {
@@ -91,17 +164,76 @@ impl ArtifactSlot {
         };
         String::from(temp)
     }
+
+    pub fn from_mona(s: &str) -> Option<ArtifactSlot> {
+        match s {
+            "flower" => Some(ArtifactSlot::Flower),
+            "feather" => Some(ArtifactSlot::Feather),
+            "sand" => Some(ArtifactSlot::Sand),
+            "cup" => Some(ArtifactSlot::Goblet),
+            "head" => Some(ArtifactSlot::Head),
+            _ => None,
+        }
+    }
 }
 
 impl Serialize for ArtifactStat {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
         let mut root = serializer.serialize_map(Some(2))?;
         root.serialize_entry("name", &self.name.to_mona());
-        root.serialize_entry("value", &self.value);
+        root.serialize_entry("value", &self.value.as_f64());
         root.end()
     }
 }
 
+impl ArtifactStat {
+    fn from_mona(value: &Value) -> Result<ArtifactStat, String> {
+        let name = value["name"].as_str().ok_or("missing stat name")?;
+        let name = ArtifactStatName::from_mona(name).ok_or(format!("unknown stat name: {}", name))?;
+        let value = value["value"].as_f64().ok_or("missing stat value")?;
+
+        Ok(ArtifactStat {
+            value: StatValue::from_f64(value, name.unit()),
+            name,
+        })
+    }
+}
+
+impl MonaArtifact {
+    pub(crate) fn from_mona(value: &Value) -> Result<MonaArtifact, String> {
+        let set_name = value["setName"].as_str().ok_or("missing setName")?;
+        let set_name = ArtifactSetName::from_mona(set_name).ok_or(format!("unknown set name: {}", set_name))?;
+        let slot = value["position"].as_str().ok_or("missing position")?;
+        let slot = ArtifactSlot::from_mona(slot).ok_or(format!("unknown position: {}", slot))?;
+        let main_stat = ArtifactStat::from_mona(&value["mainTag"])?;
+
+        let mut sub_stats: Vec<Option<ArtifactStat>> = vec![None, None, None, None];
+        if let Some(tags) = value["normalTags"].as_array() {
+            if tags.len() > 4 {
+                return Err(format!("too many normalTags: {}", tags.len()));
+            }
+            for (i, tag) in tags.iter().enumerate() {
+                sub_stats[i] = Some(ArtifactStat::from_mona(tag)?);
+            }
+        }
+        let mut sub_stats = sub_stats.into_iter();
+
+        Ok(MonaArtifact {
+            set_name,
+            slot,
+            star: value["star"].as_u64().ok_or("missing star")? as u32,
+            level: value["level"].as_u64().ok_or("missing level")? as u32,
+            main_stat,
+            sub_stat_1: sub_stats.next().unwrap(),
+            sub_stat_2: sub_stats.next().unwrap(),
+            sub_stat_3: sub_stats.next().unwrap(),
+            sub_stat_4: sub_stats.next().unwrap(),
+            // v2 writes character keys, v1 files may carry the raw name
+            equip: value["equip"].as_str().map(|e| String::from(character_name(e).unwrap_or(e))),
+        })
+    }
+}
+
 impl Serialize for MonaArtifact {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
         let mut root = serializer.serialize_map(Some(7))?;
@@ -140,13 +272,80 @@ impl Serialize for MonaArtifact {
     }
 }
 
+fn unsupported(artifact: &InternalArtifact) -> Option<String> {
+    match artifact.set_name {
+        ArtifactSetName::Unknown { .. } => Some(String::from("未知套装")),
+        _ => None,
+    }
+}
+
+pub const MONA_FORMAT_VERSIONS: [&str; 2] = ["1", "2"];
+
+// v2 adds the equipped character and a stable id to each artifact.
+// lock is left out until the scanner reads the lock state
+pub(crate) struct MonaArtifactV2<'a> {
+    artifact: &'a MonaArtifact,
+}
+
+impl<'a> Serialize for MonaArtifactV2<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let artifact = self.artifact;
+        let mut root = serializer.serialize_map(Some(9))?;
+        root.serialize_entry("id", &artifact.id())?;
+        root.serialize_entry("setName", &artifact.set_name.to_mona())?;
+        root.serialize_entry("position", &artifact.slot.to_mona())?;
+        root.serialize_entry("mainTag", &artifact.main_stat)?;
+        root.serialize_entry("normalTags", &artifact.sub_stats())?;
+        root.serialize_entry("omit", &false)?;
+        root.serialize_entry("level", &artifact.level)?;
+        root.serialize_entry("star", &artifact.star)?;
+
+        // same character keys as GOOD, e.g. "RaidenShogun"
+        let equip = match artifact.equip {
+            Some(ref name) => match character_key(name) {
+                Some(key) => Some(key),
+                None => {
+                    warn!("unknown character: {}", name);
+                    None
+                }
+            },
+            None => None,
+        };
+        root.serialize_entry("equip", &equip)?;
+        root.end()
+    }
+}
+
+pub(crate) enum MonaEntry<'a> {
+    V1(&'a MonaArtifact),
+    V2(MonaArtifactV2<'a>),
+}
+
+impl<'a> MonaEntry<'a> {
+    pub(crate) fn new(artifact: &'a MonaArtifact, version: &str) -> MonaEntry<'a> {
+        match version {
+            "2" => MonaEntry::V2(MonaArtifactV2 { artifact }),
+            _ => MonaEntry::V1(artifact),
+        }
+    }
+}
+
+impl<'a> Serialize for MonaEntry<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        match self {
+            MonaEntry::V1(artifact) => artifact.serialize(serializer),
+            MonaEntry::V2(artifact) => artifact.serialize(serializer),
+        }
+    }
+}
+
 pub struct MonaFormat<'a> {
     version: String,
-    flower: Vec<&'a MonaArtifact>,
-    feather: Vec<&'a MonaArtifact>,
-    cup: Vec<&'a MonaArtifact>,
-    sand: Vec<&'a MonaArtifact>,
-    head: Vec<&'a MonaArtifact>,
+    flower: Vec<MonaEntry<'a>>,
+    feather: Vec<MonaEntry<'a>>,
+    cup: Vec<MonaEntry<'a>>,
+    sand: Vec<MonaEntry<'a>>,
+    head: Vec<MonaEntry<'a>>,
 }
 
 impl<'a> Serialize for MonaFormat<'a> {
@@ -163,20 +362,29 @@ impl<'a> Serialize for MonaFormat<'a> {
 }
 
 impl<'a> MonaFormat<'a> {
-    pub fn new(results: &Vec<InternalArtifact>) -> MonaFormat {
-        let mut flower: Vec<&MonaArtifact> = Vec::new();
-        let mut feather: Vec<&MonaArtifact> = Vec::new();
-        let mut cup: Vec<&MonaArtifact> = Vec::new();
-        let mut sand: Vec<&MonaArtifact> = Vec::new();
-        let mut head: Vec<&MonaArtifact> = Vec::new();
+    pub fn new(results: &'a [InternalArtifact]) -> MonaFormat<'a> {
+        MonaFormat::with_version(results, "1")
+    }
+
+    // `version` is one of MONA_FORMAT_VERSIONS
+    pub fn with_version(results: &'a [InternalArtifact], version: &str) -> MonaFormat<'a> {
+        let mut flower: Vec<MonaEntry> = Vec::new();
+        let mut feather: Vec<MonaEntry> = Vec::new();
+        let mut cup: Vec<MonaEntry> = Vec::new();
+        let mut sand: Vec<MonaEntry> = Vec::new();
+        let mut head: Vec<MonaEntry> = Vec::new();
 
         for art in results.iter() {
+            if unsupported(art).is_some() {
+                continue;
+            }
+            let entry = MonaEntry::new(art, version);
             match art.slot {
-                ArtifactSlot::Flower => flower.push(art),
-                ArtifactSlot::Feather => feather.push(art),
-                ArtifactSlot::Sand => sand.push(art),
-                ArtifactSlot::Goblet => cup.push(art),
-                ArtifactSlot::Head => head.push(art),
+                ArtifactSlot::Flower => flower.push(entry),
+                ArtifactSlot::Feather => feather.push(entry),
+                ArtifactSlot::Sand => sand.push(entry),
+                ArtifactSlot::Goblet => cup.push(entry),
+                ArtifactSlot::Head => head.push(entry),
             }
         }
 
@@ -187,20 +395,126 @@ impl<'a> MonaFormat<'a> {
             sand,
             head,
 
-            version: String::from("1"),
+            version: String::from(version),
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Vec<InternalArtifact>, String> {
+        let json: Value = serde_json::from_str(s).map_err(|e| e.to_string())?;
+        if !json.is_object() {
+            return Err(String::from("mona format should be an object"));
         }
+
+        let mut results: Vec<InternalArtifact> = Vec::new();
+        for key in ["flower", "feather", "sand", "cup", "head"].iter() {
+            let arts = match json[*key].as_array() {
+                Some(v) => v,
+                None => continue,
+            };
+            for art in arts.iter() {
+                results.push(MonaArtifact::from_mona(art)?);
+            }
+        }
+
+        Ok(results)
     }
 
-    pub fn save(&self, path: String) {
-        let mut file = match File::create(&path) {
-            Err(why) => panic!("couldn't create {}: {}", path, why),
-            Ok(file) => file,
+    pub fn load(path: String) -> Result<Vec<InternalArtifact>, String> {
+        let content = match fs::read_to_string(&path) {
+            Err(why) => return Err(format!("couldn't read {}: {}", path, why)),
+            Ok(s) => s,
         };
-        let s = serde_json::to_string(&self).unwrap();
+        MonaFormat::parse(&content)
+    }
+}
 
-        match file.write_all(s.as_bytes()) {
-            Err(why) => panic!("couldn't write to {}: {}", path, why),
-            _ => {},
+pub struct MonaExporter {
+    version: &'static str,
+}
+
+impl MonaExporter {
+    pub fn new(version: &'static str) -> MonaExporter {
+        MonaExporter { version }
+    }
+}
+
+impl Exporter for MonaExporter {
+    fn name(&self) -> &'static str {
+        "mona"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn write(&self, results: &[InternalArtifact], w: &mut dyn Write) -> Result<(), String> {
+        serde_json::to_writer(w, &MonaFormat::with_version(results, self.version)).map_err(json_error)
+    }
+
+    fn unsupported(&self, artifact: &InternalArtifact) -> Option<String> {
+        unsupported(artifact)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+        "version": "1",
+        "flower": [{
+            "setName": "emblemOfSeveredFate", "position": "flower",
+            "mainTag": {"name": "lifeStatic", "value": 4780},
+            "normalTags": [
+                {"name": "critical", "value": 0.105}, {"name": "criticalDamage", "value": 0.218},
+                {"name": "recharge", "value": 0.058}, {"name": "attackStatic", "value": 19}
+            ],
+            "omit": false, "level": 20, "star": 5
+        }],
+        "feather": [],
+        "sand": [],
+        "cup": [{
+            "setName": "gladiatorFinale", "position": "cup",
+            "mainTag": {"name": "iceBonus", "value": 0.11},
+            "normalTags": [{"name": "elementalMastery", "value": 23}, {"name": "defendPercentage", "value": 0.073}],
+            "omit": false, "level": 2, "star": 5
+        }],
+        "head": [{
+            "setName": "emblemOfSeveredFate", "position": "head",
+            "mainTag": {"name": "critical", "value": 0.101},
+            "normalTags": [{"name": "lifePercentage", "value": 0.047}],
+            "omit": false, "level": 5, "star": 4, "equip": "RaidenShogun"
+        }]
+    }"#;
+
+    fn round_trip(artifacts: &[InternalArtifact], version: &'static str) -> Vec<InternalArtifact> {
+        let mut buf: Vec<u8> = Vec::new();
+        MonaExporter::new(version).write(artifacts, &mut buf).unwrap();
+        MonaFormat::parse(&String::from_utf8(buf).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parse_reads_all_slots() {
+        let artifacts = MonaFormat::parse(SAMPLE).unwrap();
+        assert_eq!(artifacts.len(), 3);
+        assert_eq!(artifacts[1].main_stat.value.to_string(), "11.0%");
+        assert_eq!(artifacts[1].sub_stats().len(), 2);
+        assert_eq!(artifacts[2].equip.as_deref(), Some("雷电将军"));
+    }
+
+    #[test]
+    fn round_trip_v1() {
+        let mut artifacts = MonaFormat::parse(SAMPLE).unwrap();
+        // v1 has no equip
+        for a in artifacts.iter_mut() {
+            a.equip = None;
         }
+        assert_eq!(round_trip(&artifacts, "1"), artifacts);
+    }
+
+    #[test]
+    fn round_trip_v2() {
+        let artifacts = MonaFormat::parse(SAMPLE).unwrap();
+        assert_eq!(round_trip(&artifacts, "2"), artifacts);
     }
 }
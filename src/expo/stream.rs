@@ -0,0 +1,98 @@
+use std::fs::File;
+use std::io::{self, Write};
+
+use log::warn;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::expo::yas::{YasArtifact, YasMeta};
+use crate::scanner::yas_scanner::{YasArtifactMeta, YasArtifactRecord, YasScanResult};
+
+// One JSON object per line, written as soon as the recognition thread has the result:
+//   {"type":"artifact","artifact":{...}}     same layout as an artifact in the yas format
+//   {"type":"duplicate","scan":{...}}
+//   {"type":"error","scan":{...}}            the artifact could not be parsed
+//   {"type":"end","count":1,"errors":0,"duplicates":0}
+enum StreamLine<'a> {
+    Artifact(&'a YasArtifactRecord),
+    Duplicate(&'a YasArtifactMeta, &'a YasScanResult),
+    Error(&'a YasArtifactMeta, &'a YasScanResult),
+    End { count: usize, errors: u32, duplicates: u32 },
+}
+
+impl<'a> Serialize for StreamLine<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(None)?;
+        match *self {
+            StreamLine::Artifact(record) => {
+                root.serialize_entry("type", "artifact")?;
+                root.serialize_entry("artifact", &YasArtifact { artifact: &record.artifact, record: Some(record) })?;
+            }
+            StreamLine::Duplicate(meta, raw) => {
+                root.serialize_entry("type", "duplicate")?;
+                root.serialize_entry("scan", &YasMeta { meta, raw })?;
+            }
+            StreamLine::Error(meta, raw) => {
+                root.serialize_entry("type", "error")?;
+                root.serialize_entry("scan", &YasMeta { meta, raw })?;
+            }
+            StreamLine::End { count, errors, duplicates } => {
+                root.serialize_entry("type", "end")?;
+                root.serialize_entry("count", &count)?;
+                root.serialize_entry("errors", &errors)?;
+                root.serialize_entry("duplicates", &duplicates)?;
+            }
+        }
+        root.end()
+    }
+}
+
+pub struct YasStream {
+    writer: Box<dyn Write + Send>,
+    failed: bool,
+}
+
+impl YasStream {
+    // "-" writes to stdout
+    pub fn open(path: &str) -> Result<YasStream, String> {
+        let writer: Box<dyn Write + Send> = if path == "-" {
+            Box::new(io::stdout())
+        } else {
+            match File::create(path) {
+                Ok(f) => Box::new(f),
+                Err(why) => return Err(format!("couldn't create {}: {}", path, why)),
+            }
+        };
+        Ok(YasStream { writer, failed: false })
+    }
+
+    fn write_line(&mut self, line: &StreamLine) {
+        if self.failed {
+            return;
+        }
+        // flush every line so that the consumer and a partial file see it immediately
+        let result = serde_json::to_writer(&mut self.writer, line)
+            .map_err(|e| e.to_string())
+            .and_then(|_| self.writer.write_all(b"\n").map_err(|e| e.to_string()))
+            .and_then(|_| self.writer.flush().map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("stream output failed, stop streaming: {}", e);
+            self.failed = true;
+        }
+    }
+
+    pub fn artifact(&mut self, record: &YasArtifactRecord) {
+        self.write_line(&StreamLine::Artifact(record));
+    }
+
+    pub fn duplicate(&mut self, meta: &YasArtifactMeta, raw: &YasScanResult) {
+        self.write_line(&StreamLine::Duplicate(meta, raw));
+    }
+
+    pub fn error(&mut self, meta: &YasArtifactMeta, raw: &YasScanResult) {
+        self.write_line(&StreamLine::Error(meta, raw));
+    }
+
+    pub fn end(&mut self, count: usize, errors: u32, duplicates: u32) {
+        self.write_line(&StreamLine::End { count, errors, duplicates });
+    }
+}
@@ -0,0 +1,599 @@
+use std::fs;
+use std::io::Write;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+
+use crate::artifact::internal_artifact::{
+    ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName, InternalArtifact, StatValue,
+};
+use crate::character::internal_character::{CharacterName, InternalCharacter};
+use crate::expo::exporter::{json_error, Exporter};
+use crate::material::internal_material::InternalMaterial;
+use crate::scanner::yas_scanner::{YasArtifactMeta, YasArtifactRecord, YasScanResult};
+use crate::weapon::internal_weapon::{InternalWeapon, WeaponName};
+
+// Bump when the layout changes, see schema/yas.schema.json
+pub const YAS_FORMAT_VERSION: u32 = 1;
+
+impl ArtifactStatName {
+    pub fn to_yas(&self) -> &'static str {
+        match self {
+            ArtifactStatName::HealingBonus => "HealingBonus",
+            ArtifactStatName::CriticalDamage => "CriticalDamage",
+            ArtifactStatName::Critical => "Critical",
+            ArtifactStatName::Atk => "Atk",
+            ArtifactStatName::AtkPercentage => "AtkPercentage",
+            ArtifactStatName::ElementalMastery => "ElementalMastery",
+            ArtifactStatName::Recharge => "Recharge",
+            ArtifactStatName::HpPercentage => "HpPercentage",
+            ArtifactStatName::Hp => "Hp",
+            ArtifactStatName::DefPercentage => "DefPercentage",
+            ArtifactStatName::Def => "Def",
+            ArtifactStatName::ElectroBonus => "ElectroBonus",
+            ArtifactStatName::PyroBonus => "PyroBonus",
+            ArtifactStatName::HydroBonus => "HydroBonus",
+            ArtifactStatName::CryoBonus => "CryoBonus",
+            ArtifactStatName::AnemoBonus => "AnemoBonus",
+            ArtifactStatName::GeoBonus => "GeoBonus",
+            ArtifactStatName::PhysicalBonus => "PhysicalBonus",
+        }
+    }
+
+    pub fn from_yas(s: &str) -> Option<ArtifactStatName> {
+        match s {
+            "HealingBonus" => Some(ArtifactStatName::HealingBonus),
+            "CriticalDamage" => Some(ArtifactStatName::CriticalDamage),
+            "Critical" => Some(ArtifactStatName::Critical),
+            "Atk" => Some(ArtifactStatName::Atk),
+            "AtkPercentage" => Some(ArtifactStatName::AtkPercentage),
+            "ElementalMastery" => Some(ArtifactStatName::ElementalMastery),
+            "Recharge" => Some(ArtifactStatName::Recharge),
+            "HpPercentage" => Some(ArtifactStatName::HpPercentage),
+            "Hp" => Some(ArtifactStatName::Hp),
+            "DefPercentage" => Some(ArtifactStatName::DefPercentage),
+            "Def" => Some(ArtifactStatName::Def),
+            "ElectroBonus" => Some(ArtifactStatName::ElectroBonus),
+            "PyroBonus" => Some(ArtifactStatName::PyroBonus),
+            "HydroBonus" => Some(ArtifactStatName::HydroBonus),
+            "CryoBonus" => Some(ArtifactStatName::CryoBonus),
+            "AnemoBonus" => Some(ArtifactStatName::AnemoBonus),
+            "GeoBonus" => Some(ArtifactStatName::GeoBonus),
+            "PhysicalBonus" => Some(ArtifactStatName::PhysicalBonus),
+            _ => None,
+        }
+    }
+}
+
+impl ArtifactSlot {
+    pub fn to_yas(&self) -> &'static str {
+        match self {
+            ArtifactSlot::Flower => "Flower",
+            ArtifactSlot::Feather => "Feather",
+            ArtifactSlot::Sand => "Sand",
+            ArtifactSlot::Goblet => "Goblet",
+            ArtifactSlot::Head => "Head",
+        }
+    }
+
+    pub fn from_yas(s: &str) -> Option<ArtifactSlot> {
+        match s {
+            "Flower" => Some(ArtifactSlot::Flower),
+            "Feather" => Some(ArtifactSlot::Feather),
+            "Sand" => Some(ArtifactSlot::Sand),
+            "Goblet" => Some(ArtifactSlot::Goblet),
+            "Head" => Some(ArtifactSlot::Head),
+            _ => None,
+        }
+    }
+}
+
+impl ArtifactSetName {
+    pub fn to_yas(&self) -> &'static str {
+        match self {
+            ArtifactSetName::ArchaicPetra => "ArchaicPetra",
+            ArtifactSetName::HeartOfDepth => "HeartOfDepth",
+            ArtifactSetName::BlizzardStrayer => "BlizzardStrayer",
+            ArtifactSetName::RetracingBolide => "RetracingBolide",
+            ArtifactSetName::NoblesseOblige => "NoblesseOblige",
+            ArtifactSetName::GladiatorFinale => "GladiatorFinale",
+            ArtifactSetName::MaidenBeloved => "MaidenBeloved",
+            ArtifactSetName::ViridescentVenerer => "ViridescentVenerer",
+            ArtifactSetName::LavaWalker => "LavaWalker",
+            ArtifactSetName::CrimsonWitch => "CrimsonWitch",
+            ArtifactSetName::ThunderSmoother => "ThunderSmoother",
+            ArtifactSetName::ThunderingFury => "ThunderingFury",
+            ArtifactSetName::BloodstainedChivalry => "BloodstainedChivalry",
+            ArtifactSetName::WandererTroupe => "WandererTroupe",
+            ArtifactSetName::Scholar => "Scholar",
+            ArtifactSetName::Gambler => "Gambler",
+            ArtifactSetName::TinyMiracle => "TinyMiracle",
+            ArtifactSetName::MartialArtist => "MartialArtist",
+            ArtifactSetName::BraveHeart => "BraveHeart",
+            ArtifactSetName::ResolutionOfSojourner => "ResolutionOfSojourner",
+            ArtifactSetName::DefenderWill => "DefenderWill",
+            ArtifactSetName::Berserker => "Berserker",
+            ArtifactSetName::Instructor => "Instructor",
+            ArtifactSetName::Exile => "Exile",
+            ArtifactSetName::Adventurer => "Adventurer",
+            ArtifactSetName::LuckyDog => "LuckyDog",
+            ArtifactSetName::TravelingDoctor => "TravelingDoctor",
+            ArtifactSetName::PrayersForWisdom => "PrayersForWisdom",
+            ArtifactSetName::PrayersToSpringtime => "PrayersToSpringtime",
+            ArtifactSetName::PrayersForIllumination => "PrayersForIllumination",
+            ArtifactSetName::PrayersForDestiny => "PrayersForDestiny",
+            ArtifactSetName::PaleFlame => "PaleFlame",
+            ArtifactSetName::TenacityOfTheMillelith => "TenacityOfTheMillelith",
+            ArtifactSetName::EmblemOfSeveredFate => "EmblemOfSeveredFate",
+            ArtifactSetName::ShimenawaReminiscence => "ShimenawaReminiscence",
+            ArtifactSetName::HuskOfOpulentDreams => "HuskOfOpulentDreams",
+            ArtifactSetName::OceanHuedClam => "OceanHuedClam",
+            ArtifactSetName::Unknown { .. } => "Unknown",
+        }
+    }
+
+    pub fn from_yas(s: &str) -> Option<ArtifactSetName> {
+        match s {
+            "ArchaicPetra" => Some(ArtifactSetName::ArchaicPetra),
+            "HeartOfDepth" => Some(ArtifactSetName::HeartOfDepth),
+            "BlizzardStrayer" => Some(ArtifactSetName::BlizzardStrayer),
+            "RetracingBolide" => Some(ArtifactSetName::RetracingBolide),
+            "NoblesseOblige" => Some(ArtifactSetName::NoblesseOblige),
+            "GladiatorFinale" => Some(ArtifactSetName::GladiatorFinale),
+            "MaidenBeloved" => Some(ArtifactSetName::MaidenBeloved),
+            "ViridescentVenerer" => Some(ArtifactSetName::ViridescentVenerer),
+            "LavaWalker" => Some(ArtifactSetName::LavaWalker),
+            "CrimsonWitch" => Some(ArtifactSetName::CrimsonWitch),
+            "ThunderSmoother" => Some(ArtifactSetName::ThunderSmoother),
+            "ThunderingFury" => Some(ArtifactSetName::ThunderingFury),
+            "BloodstainedChivalry" => Some(ArtifactSetName::BloodstainedChivalry),
+            "WandererTroupe" => Some(ArtifactSetName::WandererTroupe),
+            "Scholar" => Some(ArtifactSetName::Scholar),
+            "Gambler" => Some(ArtifactSetName::Gambler),
+            "TinyMiracle" => Some(ArtifactSetName::TinyMiracle),
+            "MartialArtist" => Some(ArtifactSetName::MartialArtist),
+            "BraveHeart" => Some(ArtifactSetName::BraveHeart),
+            "ResolutionOfSojourner" => Some(ArtifactSetName::ResolutionOfSojourner),
+            "DefenderWill" => Some(ArtifactSetName::DefenderWill),
+            "Berserker" => Some(ArtifactSetName::Berserker),
+            "Instructor" => Some(ArtifactSetName::Instructor),
+            "Exile" => Some(ArtifactSetName::Exile),
+            "Adventurer" => Some(ArtifactSetName::Adventurer),
+            "LuckyDog" => Some(ArtifactSetName::LuckyDog),
+            "TravelingDoctor" => Some(ArtifactSetName::TravelingDoctor),
+            "PrayersForWisdom" => Some(ArtifactSetName::PrayersForWisdom),
+            "PrayersToSpringtime" => Some(ArtifactSetName::PrayersToSpringtime),
+            "PrayersForIllumination" => Some(ArtifactSetName::PrayersForIllumination),
+            "PrayersForDestiny" => Some(ArtifactSetName::PrayersForDestiny),
+            "PaleFlame" => Some(ArtifactSetName::PaleFlame),
+            "TenacityOfTheMillelith" => Some(ArtifactSetName::TenacityOfTheMillelith),
+            "EmblemOfSeveredFate" => Some(ArtifactSetName::EmblemOfSeveredFate),
+            "ShimenawaReminiscence" => Some(ArtifactSetName::ShimenawaReminiscence),
+            "HuskOfOpulentDreams" => Some(ArtifactSetName::HuskOfOpulentDreams),
+            "OceanHuedClam" => Some(ArtifactSetName::OceanHuedClam),
+            _ => None,
+        }
+    }
+}
+
+pub struct YasScanMetadata {
+    pub yas_version: String,
+    // unix timestamp in milliseconds
+    pub time: u64,
+    // in seconds
+    pub duration: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+struct YasStat<'a> {
+    stat: &'a ArtifactStat,
+}
+
+impl<'a> Serialize for YasStat<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(Some(2))?;
+        root.serialize_entry("name", self.stat.name.to_yas())?;
+        // as shown in game, e.g. 46.6 for "46.6%"
+        root.serialize_entry("value", &self.stat.value.display_value())?;
+        root.end()
+    }
+}
+
+struct YasRaw<'a> {
+    raw: &'a YasScanResult,
+}
+
+impl<'a> Serialize for YasRaw<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let raw = self.raw;
+        let mut root = serializer.serialize_map(Some(9))?;
+        root.serialize_entry("title", &raw.name)?;
+        root.serialize_entry("mainStatName", &raw.main_stat_name)?;
+        root.serialize_entry("mainStatValue", &raw.main_stat_value)?;
+        root.serialize_entry("subStat1", &raw.sub_stat_1)?;
+        root.serialize_entry("subStat2", &raw.sub_stat_2)?;
+        root.serialize_entry("subStat3", &raw.sub_stat_3)?;
+        root.serialize_entry("subStat4", &raw.sub_stat_4)?;
+        root.serialize_entry("level", &raw.level)?;
+        root.serialize_entry("equip", &raw.equip)?;
+        root.end()
+    }
+}
+
+pub(crate) struct YasMeta<'a> {
+    pub(crate) meta: &'a YasArtifactMeta,
+    pub(crate) raw: &'a YasScanResult,
+}
+
+impl<'a> Serialize for YasMeta<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let meta = self.meta;
+        let mut root = serializer.serialize_map(Some(7))?;
+        root.serialize_entry("index", &meta.index)?;
+        root.serialize_entry("row", &meta.row)?;
+        root.serialize_entry("col", &meta.col)?;
+        root.serialize_entry("starDetection", &meta.star_detection)?;
+        root.serialize_entry("time", &meta.time)?;
+        root.serialize_entry("confidence", &meta.confidence)?;
+        root.serialize_entry("raw", &YasRaw { raw: self.raw })?;
+        root.end()
+    }
+}
+
+pub(crate) struct YasArtifact<'a> {
+    pub(crate) artifact: &'a InternalArtifact,
+    pub(crate) record: Option<&'a YasArtifactRecord>,
+}
+
+impl<'a> Serialize for YasArtifact<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let artifact = self.artifact;
+        let mut root = serializer.serialize_map(None)?;
+        root.serialize_entry("id", &artifact.id())?;
+        root.serialize_entry("setName", artifact.set_name.to_yas())?;
+        if let ArtifactSetName::Unknown { raw_title } = &artifact.set_name {
+            root.serialize_entry("rawTitle", raw_title)?;
+        }
+        root.serialize_entry("slot", artifact.slot.to_yas())?;
+        root.serialize_entry("star", &artifact.star)?;
+        root.serialize_entry("level", &artifact.level)?;
+        root.serialize_entry("mainStat", &YasStat { stat: &artifact.main_stat })?;
+
+        let sub_stats: Vec<YasStat> = [&artifact.sub_stat_1, &artifact.sub_stat_2, &artifact.sub_stat_3, &artifact.sub_stat_4]
+            .iter()
+            .filter_map(|s| s.as_ref())
+            .map(|stat| YasStat { stat })
+            .collect();
+        root.serialize_entry("subStats", &sub_stats)?;
+        root.serialize_entry("equip", &artifact.equip)?;
+        match self.record {
+            Some(record) => root.serialize_entry("scan", &YasMeta { meta: &record.meta, raw: &record.raw })?,
+            None => root.serialize_entry("scan", &Option::<()>::None)?,
+        }
+        root.end()
+    }
+}
+
+struct YasScan<'a> {
+    meta: &'a YasScanMetadata,
+}
+
+impl<'a> Serialize for YasScan<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let meta = self.meta;
+        let mut root = serializer.serialize_map(Some(5))?;
+        root.serialize_entry("yasVersion", &meta.yas_version)?;
+        root.serialize_entry("time", &meta.time)?;
+        root.serialize_entry("duration", &meta.duration)?;
+        root.serialize_entry("width", &meta.width)?;
+        root.serialize_entry("height", &meta.height)?;
+        root.end()
+    }
+}
+
+struct YasWeapon<'a> {
+    weapon: &'a InternalWeapon,
+}
+
+impl<'a> Serialize for YasWeapon<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let weapon = self.weapon;
+        let mut root = serializer.serialize_map(None)?;
+        root.serialize_entry("name", weapon.name.key())?;
+        if let WeaponName::Unknown { raw_title } = &weapon.name {
+            root.serialize_entry("rawTitle", raw_title)?;
+        }
+        root.serialize_entry("star", &weapon.star)?;
+        root.serialize_entry("level", &weapon.level)?;
+        root.serialize_entry("ascension", &weapon.ascension)?;
+        root.serialize_entry("refinement", &weapon.refinement)?;
+        root.serialize_entry("equip", &weapon.equip)?;
+        root.end()
+    }
+}
+
+struct YasCharacter<'a> {
+    character: &'a InternalCharacter,
+}
+
+impl<'a> Serialize for YasCharacter<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let character = self.character;
+        let mut root = serializer.serialize_map(None)?;
+        root.serialize_entry("name", character.name.key())?;
+        if let CharacterName::Unknown { raw_name } = &character.name {
+            root.serialize_entry("rawName", raw_name)?;
+        }
+        root.serialize_entry("level", &character.level)?;
+        root.serialize_entry("ascension", &character.ascension)?;
+        root.serialize_entry("constellation", &character.constellation)?;
+        root.serialize_entry("talentAuto", &character.talent_auto)?;
+        root.serialize_entry("talentSkill", &character.talent_skill)?;
+        root.serialize_entry("talentBurst", &character.talent_burst)?;
+        root.end()
+    }
+}
+
+struct YasMaterials<'a> {
+    materials: &'a [InternalMaterial],
+}
+
+impl<'a> Serialize for YasMaterials<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(Some(self.materials.len()))?;
+        for material in self.materials.iter() {
+            root.serialize_entry(&material.key, &material.count)?;
+        }
+        root.end()
+    }
+}
+
+// A file holds the artifacts, the weapons, the characters or the materials of one scan
+pub struct YasFormat<'a> {
+    scan: Option<&'a YasScanMetadata>,
+    artifacts: Option<Vec<YasArtifact<'a>>>,
+    weapons: Option<Vec<YasWeapon<'a>>>,
+    characters: Option<Vec<YasCharacter<'a>>>,
+    materials: Option<YasMaterials<'a>>,
+}
+
+impl<'a> Serialize for YasFormat<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut root = serializer.serialize_map(None)?;
+        root.serialize_entry("format", "yas")?;
+        root.serialize_entry("version", &YAS_FORMAT_VERSION)?;
+        root.serialize_entry("scan", &self.scan.map(|meta| YasScan { meta }))?;
+        if let Some(ref artifacts) = self.artifacts {
+            root.serialize_entry("artifacts", artifacts)?;
+        }
+        if let Some(ref weapons) = self.weapons {
+            root.serialize_entry("weapons", weapons)?;
+        }
+        if let Some(ref characters) = self.characters {
+            root.serialize_entry("characters", characters)?;
+        }
+        if let Some(ref materials) = self.materials {
+            root.serialize_entry("materials", materials)?;
+        }
+        root.end()
+    }
+}
+
+fn stat_from_yas(value: &Value) -> Result<ArtifactStat, String> {
+    let name = value["name"].as_str().ok_or("missing stat name")?;
+    let name = ArtifactStatName::from_yas(name).ok_or(format!("unknown stat name: {}", name))?;
+    let v = value["value"].as_f64().ok_or("missing stat value")?;
+
+    Ok(ArtifactStat {
+        value: StatValue::from_display(v, name.unit()),
+        name,
+    })
+}
+
+// The artifact together with its scan information, which has to be present
+pub(crate) fn record_from_yas(value: &Value) -> Result<YasArtifactRecord, String> {
+    let artifact = artifact_from_yas(value)?;
+    let scan = &value["scan"];
+    if !scan.is_object() {
+        return Err(String::from("missing scan"));
+    }
+    let raw = &scan["raw"];
+    let raw_field = |key: &str| -> Result<String, String> {
+        raw[key].as_str().map(String::from).ok_or(format!("missing raw {}", key))
+    };
+    let u32_field = |key: &str| -> Result<u32, String> {
+        scan[key].as_u64().map(|v| v as u32).ok_or(format!("missing {}", key))
+    };
+
+    Ok(YasArtifactRecord {
+        raw: YasScanResult {
+            name: raw_field("title")?,
+            main_stat_name: raw_field("mainStatName")?,
+            main_stat_value: raw_field("mainStatValue")?,
+            sub_stat_1: raw_field("subStat1")?,
+            sub_stat_2: raw_field("subStat2")?,
+            sub_stat_3: raw_field("subStat3")?,
+            sub_stat_4: raw_field("subStat4")?,
+            level: raw_field("level")?,
+            equip: raw_field("equip")?,
+            star: artifact.star,
+        },
+        meta: YasArtifactMeta {
+            index: u32_field("index")?,
+            row: u32_field("row")?,
+            col: u32_field("col")?,
+            star_detection: String::from(scan["starDetection"].as_str().ok_or("missing starDetection")?),
+            time: scan["time"].as_u64().ok_or("missing time")?,
+            // not written by older versions
+            confidence: scan["confidence"].as_f64().unwrap_or(0.0) as f32,
+        },
+        panel: None,
+        artifact,
+    })
+}
+
+fn artifact_from_yas(value: &Value) -> Result<InternalArtifact, String> {
+    let set_name = match value["setName"].as_str().ok_or("missing setName")? {
+        "Unknown" => ArtifactSetName::Unknown {
+            raw_title: String::from(value["rawTitle"].as_str().ok_or("missing rawTitle")?),
+        },
+        name => ArtifactSetName::from_yas(name).ok_or(format!("unknown set name: {}", name))?,
+    };
+    let slot = value["slot"].as_str().ok_or("missing slot")?;
+    let slot = ArtifactSlot::from_yas(slot).ok_or(format!("unknown slot: {}", slot))?;
+
+    let mut sub_stats: Vec<Option<ArtifactStat>> = vec![None, None, None, None];
+    if let Some(stats) = value["subStats"].as_array() {
+        if stats.len() > 4 {
+            return Err(format!("too many subStats: {}", stats.len()));
+        }
+        for (i, stat) in stats.iter().enumerate() {
+            sub_stats[i] = Some(stat_from_yas(stat)?);
+        }
+    }
+    let mut sub_stats = sub_stats.into_iter();
+
+    Ok(InternalArtifact {
+        set_name,
+        slot,
+        star: value["star"].as_u64().ok_or("missing star")? as u32,
+        level: value["level"].as_u64().ok_or("missing level")? as u32,
+        main_stat: stat_from_yas(&value["mainStat"])?,
+        sub_stat_1: sub_stats.next().unwrap(),
+        sub_stat_2: sub_stats.next().unwrap(),
+        sub_stat_3: sub_stats.next().unwrap(),
+        sub_stat_4: sub_stats.next().unwrap(),
+        equip: value["equip"].as_str().map(String::from),
+    })
+}
+
+impl<'a> YasFormat<'a> {
+    pub fn new(results: &'a [InternalArtifact]) -> YasFormat<'a> {
+        YasFormat {
+            scan: None,
+            artifacts: Some(results
+                .iter()
+                .map(|artifact| YasArtifact { artifact, record: None })
+                .collect()),
+            weapons: None,
+            characters: None,
+            materials: None,
+        }
+    }
+
+    pub fn from_records(records: &'a [YasArtifactRecord], scan: &'a YasScanMetadata) -> YasFormat<'a> {
+        YasFormat {
+            scan: Some(scan),
+            artifacts: Some(records
+                .iter()
+                .map(|record| YasArtifact { artifact: &record.artifact, record: Some(record) })
+                .collect()),
+            weapons: None,
+            characters: None,
+            materials: None,
+        }
+    }
+
+    pub fn from_weapons(weapons: &'a [InternalWeapon], scan: &'a YasScanMetadata) -> YasFormat<'a> {
+        YasFormat {
+            scan: Some(scan),
+            artifacts: None,
+            weapons: Some(weapons.iter().map(|weapon| YasWeapon { weapon }).collect()),
+            characters: None,
+            materials: None,
+        }
+    }
+
+    pub fn from_characters(characters: &'a [InternalCharacter], scan: &'a YasScanMetadata) -> YasFormat<'a> {
+        YasFormat {
+            scan: Some(scan),
+            artifacts: None,
+            weapons: None,
+            characters: Some(characters.iter().map(|character| YasCharacter { character }).collect()),
+            materials: None,
+        }
+    }
+
+    pub fn from_materials(materials: &'a [InternalMaterial], scan: &'a YasScanMetadata) -> YasFormat<'a> {
+        YasFormat {
+            scan: Some(scan),
+            artifacts: None,
+            weapons: None,
+            characters: None,
+            materials: Some(YasMaterials { materials }),
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Vec<InternalArtifact>, String> {
+        let json: Value = serde_json::from_str(s).map_err(|e| e.to_string())?;
+        if json["format"].as_str() != Some("yas") {
+            return Err(String::from("not a yas format file"));
+        }
+        let version = json["version"].as_u64().ok_or("missing version")?;
+        if version > YAS_FORMAT_VERSION as u64 {
+            return Err(format!("unsupported yas format version: {}, please update yas", version));
+        }
+
+        let arts = json["artifacts"].as_array().ok_or("missing artifacts")?;
+        let mut results: Vec<InternalArtifact> = Vec::new();
+        for art in arts.iter() {
+            results.push(artifact_from_yas(art)?);
+        }
+
+        Ok(results)
+    }
+
+    // Only files written by a scan, every artifact needs its scan information
+    pub fn parse_records(s: &str) -> Result<Vec<YasArtifactRecord>, String> {
+        let json: Value = serde_json::from_str(s).map_err(|e| e.to_string())?;
+        if json["format"].as_str() != Some("yas") {
+            return Err(String::from("not a yas format file"));
+        }
+        let version = json["version"].as_u64().ok_or("missing version")?;
+        if version > YAS_FORMAT_VERSION as u64 {
+            return Err(format!("unsupported yas format version: {}, please update yas", version));
+        }
+
+        let arts = json["artifacts"].as_array().ok_or("missing artifacts")?;
+        let mut records: Vec<YasArtifactRecord> = Vec::new();
+        for art in arts.iter() {
+            records.push(record_from_yas(art)?);
+        }
+
+        Ok(records)
+    }
+
+    pub fn load(path: String) -> Result<Vec<InternalArtifact>, String> {
+        let content = match fs::read_to_string(&path) {
+            Err(why) => return Err(format!("couldn't read {}: {}", path, why)),
+            Ok(s) => s,
+        };
+        YasFormat::parse(&content)
+    }
+}
+
+pub struct YasExporter;
+
+impl Exporter for YasExporter {
+    fn name(&self) -> &'static str {
+        "yas"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn write(&self, results: &[InternalArtifact], w: &mut dyn Write) -> Result<(), String> {
+        serde_json::to_writer(w, &YasFormat::new(results)).map_err(json_error)
+    }
+
+    fn write_scan(
+        &self,
+        records: &[YasArtifactRecord],
+        metadata: &YasScanMetadata,
+        w: &mut dyn Write,
+    ) -> Result<(), String> {
+        serde_json::to_writer(w, &YasFormat::from_records(records, metadata)).map_err(json_error)
+    }
+}
@@ -0,0 +1,296 @@
+use rusqlite::{params, Connection, Row, ToSql};
+
+use crate::artifact::internal_artifact::{
+    ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName, InternalArtifact, StatValue,
+};
+use crate::expo::yas::YasScanMetadata;
+
+// names are stored with the same keys as the yas format, values as displayed in game
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scan (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    time INTEGER NOT NULL,
+    yas_version TEXT NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    account TEXT
+);
+CREATE TABLE IF NOT EXISTS artifact (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    scan_id INTEGER NOT NULL REFERENCES scan(id) ON DELETE CASCADE,
+    set_name TEXT NOT NULL,
+    raw_title TEXT,
+    slot TEXT NOT NULL,
+    star INTEGER NOT NULL,
+    level INTEGER NOT NULL,
+    main_stat TEXT NOT NULL,
+    main_stat_value REAL NOT NULL,
+    equip TEXT
+);
+CREATE TABLE IF NOT EXISTS sub_stat (
+    artifact_id INTEGER NOT NULL REFERENCES artifact(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    value REAL NOT NULL,
+    PRIMARY KEY (artifact_id, position)
+);
+CREATE INDEX IF NOT EXISTS artifact_scan ON artifact(scan_id);
+";
+
+pub struct ScanRecord {
+    pub id: i64,
+    // local time, formatted by sqlite
+    pub time: String,
+    pub yas_version: String,
+    pub width: u32,
+    pub height: u32,
+    pub account: Option<String>,
+    pub count: u32,
+}
+
+#[derive(Default)]
+pub struct HistoryQuery {
+    pub scan_id: Option<i64>,
+    pub account: Option<String>,
+    pub set_name: Option<ArtifactSetName>,
+    pub slot: Option<ArtifactSlot>,
+    pub main_stat: Option<ArtifactStatName>,
+}
+
+pub struct HistoryArtifact {
+    pub scan_id: i64,
+    pub artifact: InternalArtifact,
+}
+
+pub struct ScanHistory {
+    conn: Connection,
+}
+
+fn db_error(e: rusqlite::Error) -> String {
+    format!("数据库错误: {}", e)
+}
+
+fn stat_from_row(name: &str, value: f64) -> Result<ArtifactStat, String> {
+    let name = match ArtifactStatName::from_yas(name) {
+        Some(v) => v,
+        None => return Err(format!("unknown stat name `{}`", name)),
+    };
+    let value = StatValue::from_display(value, name.unit());
+    Ok(ArtifactStat { name, value })
+}
+
+impl ScanHistory {
+    pub fn open(path: &str) -> Result<ScanHistory, String> {
+        let conn = Connection::open(path).map_err(db_error)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;").map_err(db_error)?;
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        Ok(ScanHistory { conn })
+    }
+
+    pub fn add_scan(
+        &mut self,
+        metadata: &YasScanMetadata,
+        account: Option<&str>,
+        artifacts: &[InternalArtifact],
+    ) -> Result<i64, String> {
+        let tx = self.conn.transaction().map_err(db_error)?;
+        tx.execute(
+            "INSERT INTO scan (time, yas_version, width, height, account) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![metadata.time as i64, metadata.yas_version, metadata.width, metadata.height, account],
+        ).map_err(db_error)?;
+        let scan_id = tx.last_insert_rowid();
+
+        {
+            let mut insert_artifact = tx.prepare(
+                "INSERT INTO artifact (scan_id, set_name, raw_title, slot, star, level, main_stat, main_stat_value, equip)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+            ).map_err(db_error)?;
+            let mut insert_sub_stat = tx.prepare(
+                "INSERT INTO sub_stat (artifact_id, position, name, value) VALUES (?1, ?2, ?3, ?4)"
+            ).map_err(db_error)?;
+
+            for art in artifacts.iter() {
+                let raw_title = match art.set_name {
+                    ArtifactSetName::Unknown { ref raw_title } => Some(raw_title.as_str()),
+                    _ => None,
+                };
+                insert_artifact.execute(params![
+                    scan_id,
+                    art.set_name.to_yas(),
+                    raw_title,
+                    art.slot.to_yas(),
+                    art.star,
+                    art.level,
+                    art.main_stat.name.to_yas(),
+                    art.main_stat.value.display_value(),
+                    art.equip,
+                ]).map_err(db_error)?;
+                let artifact_id = tx.last_insert_rowid();
+
+                for (i, stat) in art.sub_stats().iter().enumerate() {
+                    insert_sub_stat.execute(params![
+                        artifact_id,
+                        i as u32,
+                        stat.name.to_yas(),
+                        stat.value.display_value(),
+                    ]).map_err(db_error)?;
+                }
+            }
+        }
+
+        tx.commit().map_err(db_error)?;
+        Ok(scan_id)
+    }
+
+    pub fn scans(&self) -> Result<Vec<ScanRecord>, String> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, datetime(s.time / 1000, 'unixepoch', 'localtime'), s.yas_version, s.width, s.height, s.account,
+                    (SELECT COUNT(*) FROM artifact a WHERE a.scan_id = s.id)
+             FROM scan s ORDER BY s.id"
+        ).map_err(db_error)?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ScanRecord {
+                id: row.get(0)?,
+                time: row.get(1)?,
+                yas_version: row.get(2)?,
+                width: row.get(3)?,
+                height: row.get(4)?,
+                account: row.get(5)?,
+                count: row.get(6)?,
+            })
+        }).map_err(db_error)?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(db_error)
+    }
+
+    pub fn artifacts(&self, query: &HistoryQuery) -> Result<Vec<HistoryArtifact>, String> {
+        let mut sql = String::from(
+            "SELECT a.id, a.scan_id, a.set_name, a.raw_title, a.slot, a.star, a.level, a.main_stat, a.main_stat_value, a.equip
+             FROM artifact a JOIN scan s ON a.scan_id = s.id WHERE 1 = 1"
+        );
+        let mut args: Vec<Box<dyn ToSql>> = Vec::new();
+        if let Some(id) = query.scan_id {
+            args.push(Box::new(id));
+            sql += &format!(" AND a.scan_id = ?{}", args.len());
+        }
+        if let Some(ref account) = query.account {
+            args.push(Box::new(account.clone()));
+            sql += &format!(" AND s.account = ?{}", args.len());
+        }
+        if let Some(ref set_name) = query.set_name {
+            args.push(Box::new(set_name.to_yas()));
+            sql += &format!(" AND a.set_name = ?{}", args.len());
+        }
+        if let Some(ref slot) = query.slot {
+            args.push(Box::new(slot.to_yas()));
+            sql += &format!(" AND a.slot = ?{}", args.len());
+        }
+        if let Some(ref main_stat) = query.main_stat {
+            args.push(Box::new(main_stat.to_yas()));
+            sql += &format!(" AND a.main_stat = ?{}", args.len());
+        }
+        sql += " ORDER BY a.scan_id, a.id";
+
+        let mut stmt = self.conn.prepare(&sql).map_err(db_error)?;
+        let mut sub_stmt = self.conn.prepare(
+            "SELECT name, value FROM sub_stat WHERE artifact_id = ?1 ORDER BY position"
+        ).map_err(db_error)?;
+
+        let params = args.iter().map(|a| a.as_ref()).collect::<Vec<&dyn ToSql>>();
+        let mut rows = stmt.query(params.as_slice()).map_err(db_error)?;
+        let mut ret: Vec<HistoryArtifact> = Vec::new();
+        while let Some(row) = rows.next().map_err(db_error)? {
+            let artifact_id: i64 = row.get(0).map_err(db_error)?;
+            let mut sub_stats: Vec<Option<ArtifactStat>> = Vec::new();
+            let mut sub_rows = sub_stmt.query(params![artifact_id]).map_err(db_error)?;
+            while let Some(sub) = sub_rows.next().map_err(db_error)? {
+                let name: String = sub.get(0).map_err(db_error)?;
+                sub_stats.push(Some(stat_from_row(&name, sub.get(1).map_err(db_error)?)?));
+            }
+            sub_stats.resize(4, None);
+
+            ret.push(HistoryArtifact {
+                scan_id: row.get(1).map_err(db_error)?,
+                artifact: artifact_from_row(row, sub_stats)?,
+            });
+        }
+
+        Ok(ret)
+    }
+}
+
+fn artifact_from_row(row: &Row, sub_stats: Vec<Option<ArtifactStat>>) -> Result<InternalArtifact, String> {
+    let set_name: String = row.get(2).map_err(db_error)?;
+    let raw_title: Option<String> = row.get(3).map_err(db_error)?;
+    let slot: String = row.get(4).map_err(db_error)?;
+    let main_stat: String = row.get(7).map_err(db_error)?;
+
+    let set_name = match set_name.as_str() {
+        "Unknown" => match raw_title {
+            Some(raw_title) => ArtifactSetName::Unknown { raw_title },
+            None => return Err(String::from("missing raw title for unknown set")),
+        },
+        name => match ArtifactSetName::from_yas(name) {
+            Some(v) => v,
+            None => return Err(format!("unknown set name `{}`", name)),
+        },
+    };
+    let slot = match ArtifactSlot::from_yas(&slot) {
+        Some(v) => v,
+        None => return Err(format!("unknown slot `{}`", slot)),
+    };
+
+    let mut sub_stats = sub_stats.into_iter();
+    Ok(InternalArtifact {
+        set_name,
+        slot,
+        star: row.get(5).map_err(db_error)?,
+        level: row.get(6).map_err(db_error)?,
+        main_stat: stat_from_row(&main_stat, row.get(8).map_err(db_error)?)?,
+        sub_stat_1: sub_stats.next().unwrap(),
+        sub_stat_2: sub_stats.next().unwrap(),
+        sub_stat_3: sub_stats.next().unwrap(),
+        sub_stat_4: sub_stats.next().unwrap(),
+        equip: row.get(9).map_err(db_error)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(name: ArtifactStatName, value: f64) -> ArtifactStat {
+        let value = StatValue::from_display(value, name.unit());
+        ArtifactStat { name, value }
+    }
+
+    #[test]
+    fn unknown_set_round_trip() {
+        let artifact = InternalArtifact {
+            set_name: ArtifactSetName::Unknown { raw_title: String::from("未知之花") },
+            slot: ArtifactSlot::Flower,
+            star: 5,
+            level: 20,
+            main_stat: stat(ArtifactStatName::Hp, 4780.0),
+            sub_stat_1: Some(stat(ArtifactStatName::Critical, 10.5)),
+            sub_stat_2: Some(stat(ArtifactStatName::CriticalDamage, 21.8)),
+            sub_stat_3: None,
+            sub_stat_4: None,
+            equip: None,
+        };
+        let metadata = YasScanMetadata {
+            yas_version: String::from("test"),
+            time: 0,
+            duration: 0.0,
+            width: 1920,
+            height: 1080,
+        };
+
+        let mut history = ScanHistory::open(":memory:").unwrap();
+        let scan_id = history.add_scan(&metadata, None, &[artifact.clone()]).unwrap();
+        let artifacts = history.artifacts(&HistoryQuery::default()).unwrap();
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].scan_id, scan_id);
+        assert_eq!(artifacts[0].artifact, artifact);
+    }
+}
@@ -58,6 +58,11 @@ impl CRNNModel {
     }
 
     pub fn inference_string(&self, img: &RawImage) -> String {
+        self.inference_string_with_confidence(img).0
+    }
+
+    // confidence is the lowest probability among the chosen characters of each time step
+    pub fn inference_string_with_confidence(&self, img: &RawImage) -> (String, f32) {
         let tensor: Tensor = tract_ndarray::Array4::from_shape_fn((1, 1, 32, 384), |(_, _, y, x)| {
             let index = img.w * y as u32 + x as u32;
             img.data[index as usize]
@@ -70,9 +75,10 @@ impl CRNNModel {
 
         let mut ans = String::new();
         let mut last_word = String::new();
+        let mut confidence: f32 = 1.0;
         for i in 0..shape[0] {
             let mut max_index = 0;
-            let mut max_value = -1.0;
+            let mut max_value = f32::NEG_INFINITY;
             for j in 0..self.index_2_word.len() {
                 let value = arr[[i, 0, j]];
                 if value > max_value {
@@ -80,6 +86,10 @@ impl CRNNModel {
                     max_index = j;
                 }
             }
+
+            // the model ends with log_softmax (trained with CTC loss), so this is the log probability
+            confidence = confidence.min(max_value.exp());
+
             let word = &self.index_2_word[max_index];
             if *word != last_word && word != "-" {
                 ans = ans + word;
@@ -88,6 +98,6 @@ impl CRNNModel {
             last_word = word.clone();
         }
 
-        ans
+        (ans, confidence)
     }
-}
\ No newline at end of file
+}
@@ -20,6 +20,25 @@ pub struct ScanInfo {
     pub equip_position: PixelRectBound,
     pub art_count_position: PixelRectBound,
 
+    // the weapon panel shares the title and equip position with artifacts
+    pub weapon_level_position: PixelRectBound,
+    pub weapon_refinement_position: PixelRectBound,
+
+    // character screen, the talents are listed as normal attack, skill, (sprint), burst
+    pub character_name_position: PixelRectBound,
+    pub character_level_position: PixelRectBound,
+    pub character_talent_positions: [PixelRectBound; 4],
+    // lit when the constellation is activated
+    pub character_constellation_points: [(u32, u32); 6],
+    // tabs of the menu on the left
+    pub character_tab_x: u32,
+    pub character_attribute_tab_y: u32,
+    pub character_constellation_tab_y: u32,
+    pub character_talent_tab_y: u32,
+    // button switching to the next character
+    pub character_next_x: u32,
+    pub character_next_y: u32,
+
     pub art_width: u32,
     pub art_height: u32,
     pub art_gap_x: u32,
@@ -36,9 +55,6 @@ pub struct ScanInfo {
     pub left: i32,
     pub top: i32,
 
-    pub flag_x: u32,
-    pub flag_y: u32,
-
     pub star_x: u32,
     pub star_y: u32,
 
@@ -90,6 +106,32 @@ impl ScanInfo {
             equip_position: my_get_rect((1143, 759, 1390, 791)),
             art_count_position: my_get_rect((1315, 26, 1503, 51)),
 
+            weapon_level_position: my_get_rect((1099, 382, 1200, 404)),
+            weapon_refinement_position: my_get_rect((1099, 412, 1200, 434)),
+
+            character_name_position: my_get_rect((1105, 95, 1400, 135)),
+            character_level_position: my_get_rect((1105, 175, 1300, 205)),
+            character_talent_positions: [
+                my_get_rect((1265, 165, 1330, 190)),
+                my_get_rect((1265, 250, 1330, 275)),
+                my_get_rect((1265, 335, 1330, 360)),
+                my_get_rect((1265, 420, 1330, 445)),
+            ],
+            character_constellation_points: [
+                (get_scalar(1195.0, w, width), get_scalar(220.0, h, height)),
+                (get_scalar(1255.0, w, width), get_scalar(320.0, h, height)),
+                (get_scalar(1285.0, w, width), get_scalar(420.0, h, height)),
+                (get_scalar(1285.0, w, width), get_scalar(525.0, h, height)),
+                (get_scalar(1255.0, w, width), get_scalar(625.0, h, height)),
+                (get_scalar(1195.0, w, width), get_scalar(725.0, h, height)),
+            ],
+            character_tab_x: get_scalar(110.0, w, width),
+            character_attribute_tab_y: get_scalar(130.0, h, height),
+            character_constellation_tab_y: get_scalar(310.0, h, height),
+            character_talent_tab_y: get_scalar(370.0, h, height),
+            character_next_x: get_scalar(1560.0, w, width),
+            character_next_y: get_scalar(450.0, h, height),
+
             art_width: get_scalar(102.5, w, width),
             art_height: get_scalar(126.0, h, height),
             art_gap_x: get_scalar(19.4, w, width),
@@ -106,9 +148,6 @@ impl ScanInfo {
             left,
             top,
 
-            flag_x: get_scalar(224.0, w, width),
-            flag_y: get_scalar(87.0, h, height),
-
             star_x: get_scalar(1452.0, w, width),
             star_y: get_scalar(124.0, h, height),
 
@@ -144,6 +183,32 @@ impl ScanInfo {
             equip_position: my_get_rect((1028, 777, 1189, 799)),
             art_count_position: my_get_rect((1173, 25, 1351, 45)),
 
+            weapon_level_position: my_get_rect((990, 344, 1087, 364)),
+            weapon_refinement_position: my_get_rect((990, 371, 1087, 391)),
+
+            character_name_position: my_get_rect((994, 86, 1260, 122)),
+            character_level_position: my_get_rect((994, 158, 1170, 184)),
+            character_talent_positions: [
+                my_get_rect((1138, 148, 1197, 171)),
+                my_get_rect((1138, 225, 1197, 248)),
+                my_get_rect((1138, 302, 1197, 324)),
+                my_get_rect((1138, 378, 1197, 400)),
+            ],
+            character_constellation_points: [
+                (get_scalar(1076.0, w, width), get_scalar(198.0, h, height)),
+                (get_scalar(1130.0, w, width), get_scalar(288.0, h, height)),
+                (get_scalar(1156.0, w, width), get_scalar(378.0, h, height)),
+                (get_scalar(1156.0, w, width), get_scalar(472.0, h, height)),
+                (get_scalar(1130.0, w, width), get_scalar(562.0, h, height)),
+                (get_scalar(1076.0, w, width), get_scalar(652.0, h, height)),
+            ],
+            character_tab_x: get_scalar(99.0, w, width),
+            character_attribute_tab_y: get_scalar(117.0, h, height),
+            character_constellation_tab_y: get_scalar(279.0, h, height),
+            character_talent_tab_y: get_scalar(333.0, h, height),
+            character_next_x: get_scalar(1404.0, w, width),
+            character_next_y: get_scalar(405.0, h, height),
+
             art_width: get_scalar(92.0, w, width),
             art_height: get_scalar(115.0, h, height),
             art_gap_x: get_scalar(17.0, w, width),
@@ -160,9 +225,6 @@ impl ScanInfo {
             left,
             top,
 
-            flag_x: get_scalar(312.0, w, width),
-            flag_y: get_scalar(87.0, h, height),
-
             star_x: get_scalar(1310.0, w, width),
             star_y: get_scalar(111.0, h, height),
 
@@ -195,6 +257,32 @@ impl ScanInfo {
             equip_position: my_get_rect((913, 850, 1057, 870)),
             art_count_position: my_get_rect((1057, 21, 1204, 41)),
 
+            weapon_level_position: my_get_rect((880, 305, 962, 323)),
+            weapon_refinement_position: my_get_rect((880, 329, 962, 347)),
+
+            character_name_position: my_get_rect((884, 76, 1120, 108)),
+            character_level_position: my_get_rect((884, 140, 1040, 164)),
+            character_talent_positions: [
+                my_get_rect((1012, 132, 1064, 152)),
+                my_get_rect((1012, 200, 1064, 220)),
+                my_get_rect((1012, 268, 1064, 288)),
+                my_get_rect((1012, 336, 1064, 356)),
+            ],
+            character_constellation_points: [
+                (get_scalar(956.0, w, width), get_scalar(176.0, h, height)),
+                (get_scalar(1004.0, w, width), get_scalar(256.0, h, height)),
+                (get_scalar(1028.0, w, width), get_scalar(336.0, h, height)),
+                (get_scalar(1028.0, w, width), get_scalar(420.0, h, height)),
+                (get_scalar(1004.0, w, width), get_scalar(500.0, h, height)),
+                (get_scalar(956.0, w, width), get_scalar(580.0, h, height)),
+            ],
+            character_tab_x: get_scalar(88.0, w, width),
+            character_attribute_tab_y: get_scalar(104.0, h, height),
+            character_constellation_tab_y: get_scalar(248.0, h, height),
+            character_talent_tab_y: get_scalar(296.0, h, height),
+            character_next_x: get_scalar(1248.0, w, width),
+            character_next_y: get_scalar(360.0, h, height),
+
             art_width: get_scalar(82.0, w, width),
             art_height: get_scalar(102.0, h, height),
             art_gap_x: get_scalar(15.0, w, width),
@@ -211,9 +299,6 @@ impl ScanInfo {
             left,
             top,
 
-            flag_x: get_scalar(277.0, w, width),
-            flag_y: get_scalar(77.0, h, height),
-
             star_x: get_scalar(1162.0, w, width),
             star_y: get_scalar(100.0, h, height),
 
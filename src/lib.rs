@@ -4,4 +4,8 @@ pub mod inference;
 pub mod info;
 pub mod scanner;
 pub mod artifact;
+pub mod weapon;
+pub mod character;
+pub mod material;
 pub mod expo;
+pub mod history;
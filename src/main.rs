@@ -1,6 +1,7 @@
+use std::fs;
 use std::io::stdin;
 use std::path::Path;
-use std::time::SystemTime;
+use std::time::{SystemTime, UNIX_EPOCH};
 extern crate native_windows_derive as nwd;
 extern crate native_windows_gui as nwg;
 use nwd::NwgUi;
@@ -10,20 +11,27 @@ use yas::common::utils;
 //use yas::inference::pre_process::{image_to_raw};
 use yas::info::info;
 //use yas::common::{RawImage};
-use yas::scanner::yas_scanner::{YasScanner, YasScannerConfig};
-
-use yas::expo::mingyu_lab::MingyuLabFormat;
-use yas::expo::mona_uranai::MonaFormat;
+use yas::scanner::yas_scanner::{ScanCoverage, YasScanner, YasScannerConfig};
+
+use yas::expo::yas::{YasFormat, YasScanMetadata};
+use yas::expo::good::GOODFormat;
+use yas::expo::mona_uranai::MONA_FORMAT_VERSIONS;
+use yas::expo::mona_merge::MonaMerge;
+use yas::expo::exporter::{save, save_scan, ExportOptions, ExporterRegistry};
+use yas::expo::{load_artifacts, load_artifacts_with_format, NO_MAIN_STAT_VALUE_FORMATS, READ_FORMATS};
+use yas::artifact::diff::ArtifactDiff;
+use yas::artifact::internal_artifact::{ArtifactSetName, ArtifactSlot, ArtifactStatName, InternalArtifact};
+use yas::history::scan_history::{HistoryQuery, ScanHistory};
 
 use winapi::um::winuser::{SetForegroundWindow, SetProcessDPIAware, ShowWindow, SW_RESTORE};
 
 use winapi::um::shellscalingapi::{SetProcessDpiAwareness, PROCESS_PER_MONITOR_DPI_AWARE};
 
-use clap::{App, Arg};
+use clap::{App, Arg, ArgMatches, SubCommand};
 
 //use image::imageops::grayscale;
 use env_logger::Builder;
-use log::{info, LevelFilter};
+use log::{error, info, warn, LevelFilter};
 use os_info;
 
 #[derive(Default, NwgUi)]
@@ -114,7 +122,7 @@ impl YasApp {
         if self.ymlab.check_state() == nwg::RadioButtonState::Checked {
             config.format = Some(String::from("mingyulab"));
         }
-        do_scan(config);
+        do_scan(config, ExportOptions::default());
         nwg::modal_info_message(&self.window, "Done", "识别结束!");
     }
 
@@ -162,7 +170,7 @@ fn get_version() -> String {
     String::from("unknown_version")
 }
 
-fn do_scan(config: YasScannerConfig) {
+fn do_scan(config: YasScannerConfig, options: ExportOptions) {
     set_dpi_awareness();
 
     let hwnd = match utils::find_window(String::from("原神")) {
@@ -211,33 +219,110 @@ fn do_scan(config: YasScannerConfig) {
     let output_format = config.format.clone();
     let output_dir = config.output_dir.clone();
 
+    let mode = config.mode.clone();
+    let filter = config.filter.clone();
     let mut scanner = YasScanner::new(info.clone(), config);
 
+    if mode != "artifact" {
+        let now = SystemTime::now();
+        let (weapons, characters, materials) = match mode.as_str() {
+            "weapon" => (scanner.start_weapons(), Vec::new(), Vec::new()),
+            "character" => (Vec::new(), scanner.start_characters(), Vec::new()),
+            _ => (Vec::new(), Vec::new(), scanner.start_materials()),
+        };
+        let t = now.elapsed().unwrap().as_secs_f64();
+        info!("time: {}s", t);
+
+        let scan_metadata = YasScanMetadata {
+            yas_version: get_version(),
+            time: now.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+            duration: t,
+            width: info.width,
+            height: info.height,
+        };
+        let output_format = output_format.expect("Unreachable");
+        let output_dir = output_dir.expect("Unreachable");
+        let output_dir = Path::new(&output_dir);
+        if mode == "weapon" {
+            let good = GOODFormat::from_weapons(&weapons);
+            let yas = YasFormat::from_weapons(&weapons, &scan_metadata);
+            save_good_yas("weapons", &good, &yas, &output_format, output_dir);
+        } else if mode == "character" {
+            let good = GOODFormat::from_characters(&characters);
+            let yas = YasFormat::from_characters(&characters, &scan_metadata);
+            save_good_yas("characters", &good, &yas, &output_format, output_dir);
+        } else {
+            let good = GOODFormat::from_materials(&materials);
+            let yas = YasFormat::from_materials(&materials, &scan_metadata);
+            save_good_yas("materials", &good, &yas, &output_format, output_dir);
+        }
+        return;
+    }
+
     let now = SystemTime::now();
-    let results = scanner.start();
+    let mut records = scanner.start_with_records();
     let t = now.elapsed().unwrap().as_secs_f64();
     info!("time: {}s", t);
 
-    let output_format = output_format.expect("Unreachable");
+    // the merge needs everything the scan saw, the filter only applies to the exports
     let output_dir = output_dir.expect("Unreachable");
     let output_dir = Path::new(&output_dir);
+    if let Some(ref path) = options.merge_into {
+        let scanned = records.iter().map(|r| r.artifact.clone()).collect::<Vec<_>>();
+        let merged = match scanner.coverage() {
+            Some(coverage) => merge_mona(path, &scanned, &coverage, options.mona_version, output_dir),
+            None => Err(format!("扫描未完成（中断、翻页出错或达到--max-row），为避免删除未扫描到的圣遗物，不合并到 {}", path)),
+        };
+        if let Err(e) = merged {
+            error!("{}", e);
+        }
+    }
+
+    if let Some(filter) = filter {
+        let count = records.len();
+        records.retain(|r| filter.matches(&r.artifact));
+        info!("筛选后保留{}个圣遗物，共识别{}个", records.len(), count);
+    }
+
+    let results = records.iter().map(|r| r.artifact.clone()).collect::<Vec<_>>();
+    let scan_metadata = YasScanMetadata {
+        yas_version: get_version(),
+        time: now.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+        duration: t,
+        width: info.width,
+        height: info.height,
+    };
+
+    let output_format = output_format.expect("Unreachable");
 
     // if let Some(config_output_dir) = config.output_dir {
     //     output_dir = Path::new(&config_output_dir);
     // }
 
-    match output_format.as_str() {
-        "mona" => {
-            let output_filename = output_dir.join("mona.json");
-            let mona = MonaFormat::new(&results);
-            mona.save(String::from(output_filename.to_str().unwrap()));
+    let registry = ExporterRegistry::with_options(&options);
+
+    for name in output_format.split(',') {
+        let exporter = match registry.get(name) {
+            Some(e) => e,
+            None => {
+                error!("unknown output format: {}", name);
+                continue;
+            }
+        };
+        let output_filename = output_dir.join(exporter.file_name());
+        match save_scan(exporter, &records, &scan_metadata, &output_filename) {
+            Ok(_) => info!("已导出 {}", output_filename.display()),
+            Err(e) => error!("{}", e),
         }
-        "mingyulab" => {
-            let output_filename = output_dir.join("mingyulab.json");
-            let mingyulab = MingyuLabFormat::new(&results);
-            mingyulab.save(String::from(output_filename.to_str().unwrap()));
+    }
+
+    if let Some(ref db) = options.db {
+        let added = ScanHistory::open(db)
+            .and_then(|mut h| h.add_scan(&scan_metadata, options.account.as_deref(), &results));
+        match added {
+            Ok(id) => info!("扫描结果已写入数据库 {}，编号 {}", db, id),
+            Err(e) => utils::error_and_quit(&e),
         }
-        _ => (),
     }
     // let info = info;
     // let img = info.art_count_position.capture_relative(&info).unwrap();
@@ -247,11 +332,154 @@ fn do_scan(config: YasScannerConfig) {
     // println!("{}", s);
 }
 
+// Weapons, characters and materials are only written as good and yas, e.g. to good_weapons.json
+fn save_good_yas(kind: &str, good: &GOODFormat, yas: &YasFormat, output_format: &str, output_dir: &Path) {
+    for name in output_format.split(',') {
+        let content = match name {
+            "good" => serde_json::to_string(good),
+            "yas" => serde_json::to_string(yas),
+            _ => {
+                error!("{}格式只能导出圣遗物", name);
+                continue;
+            }
+        };
+        let path = output_dir.join(format!("{}_{}.json", name, kind));
+        match content.map_err(|e| e.to_string()).and_then(|s| fs::write(&path, s).map_err(|e| e.to_string())) {
+            Ok(_) => info!("已导出 {}", path.display()),
+            Err(e) => error!("couldn't write to {}: {}", path.display(), e),
+        }
+    }
+}
+
+// Update an existing mona file in place, a backup is kept next to it
+fn merge_mona(
+    path: &str,
+    results: &[InternalArtifact],
+    coverage: &ScanCoverage,
+    version: &str,
+    output_dir: &Path,
+) -> Result<(), String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+    let merge = MonaMerge::new(&content, results, coverage, version)?;
+
+    let backup = format!("{}.bak", path);
+    fs::copy(path, &backup).map_err(|e| format!("couldn't write to {}: {}", backup, e))?;
+    let merged = serde_json::to_string(&merge.merged).unwrap();
+    fs::write(path, merged).map_err(|e| format!("couldn't write to {}: {}", path, e))?;
+
+    let summary = merge.to_string();
+    let summary_path = output_dir.join("merge_summary.txt");
+    fs::write(&summary_path, &summary).map_err(|e| format!("couldn't write to {}: {}", summary_path.display(), e))?;
+
+    info!("已合并到 {}，原文件备份为 {}", path, backup);
+    for line in summary.lines().take(2) {
+        info!("{}", line);
+    }
+    info!("详细变化见 {}", summary_path.display());
+    Ok(())
+}
+
+fn do_convert(matches: &ArgMatches) {
+    let input = matches.value_of("input").unwrap();
+    let output = matches.value_of("output").unwrap();
+    let (artifacts, from) = match load_artifacts_with_format(input, matches.value_of("from")) {
+        Ok(v) => v,
+        Err(e) => utils::error_and_quit(&e),
+    };
+    let to = matches.value_of("to").unwrap();
+
+    let registry = ExporterRegistry::default();
+    let exporter = registry.get(to).expect("Unreachable");
+    if let Err(e) = save(exporter, &artifacts, Path::new(output)) {
+        utils::error_and_quit(&e);
+    }
+    let skipped = artifacts.iter().filter(|a| exporter.unsupported(a).is_some()).count();
+    // the values written are the table values for the level, which may differ from the game by rounding
+    let approximated = if NO_MAIN_STAT_VALUE_FORMATS.contains(&from.as_str()) && !NO_MAIN_STAT_VALUE_FORMATS.contains(&to) {
+        artifacts.len() - skipped
+    } else {
+        0
+    };
+    if approximated > 0 {
+        warn!("{}格式不含主词条数值，{}个圣遗物的主词条数值按星级和等级推算，可能与游戏内显示相差0.1", from, approximated);
+    }
+    info!(
+        "已转换 {} 个圣遗物，{} 个无法表示，{} 个主词条数值为推算值，输出到 {}",
+        artifacts.len() - skipped, skipped, approximated, output
+    );
+}
+
+fn do_diff(matches: &ArgMatches) {
+    let format = matches.value_of("input-format");
+    let load = |path: &str| match load_artifacts(path, format) {
+        Ok(v) => v,
+        Err(e) => utils::error_and_quit(&e),
+    };
+    let old = load(matches.value_of("old").unwrap());
+    let new = load(matches.value_of("new").unwrap());
+
+    let diff = ArtifactDiff::new(&old, &new);
+    if matches.is_present("json") {
+        println!("{}", serde_json::to_string(&diff).unwrap());
+    } else {
+        print!("{}", diff);
+    }
+}
+
+fn do_history(matches: &ArgMatches) {
+    let history = match ScanHistory::open(matches.value_of("db").unwrap()) {
+        Ok(h) => h,
+        Err(e) => utils::error_and_quit(&e),
+    };
+
+    let filtered = ["scan", "account", "set", "slot", "main"].iter().any(|n| matches.is_present(n));
+    if !filtered {
+        let scans = match history.scans() {
+            Ok(v) => v,
+            Err(e) => utils::error_and_quit(&e),
+        };
+        for scan in scans.iter() {
+            println!("#{} {} v{} {}x{} {} {}",
+                     scan.id, scan.time, scan.yas_version, scan.width, scan.height,
+                     scan.account.as_deref().unwrap_or("-"), scan.count);
+        }
+        return;
+    }
+
+    let mut query = HistoryQuery::default();
+    query.scan_id = matches.value_of("scan").map(|s| match s.parse::<i64>() {
+        Ok(v) => v,
+        Err(_) => utils::error_and_quit("无效的扫描编号"),
+    });
+    query.account = matches.value_of("account").map(|s| s.to_string());
+    query.set_name = matches.value_of("set").map(|s| match ArtifactSetName::from_yas(s) {
+        Some(v) => v,
+        None => utils::error_and_quit(&format!("未知的圣遗物套装：{}", s)),
+    });
+    query.slot = matches.value_of("slot").map(|s| match ArtifactSlot::from_yas(s) {
+        Some(v) => v,
+        None => utils::error_and_quit(&format!("未知的圣遗物位置：{}", s)),
+    });
+    query.main_stat = matches.value_of("main").map(|s| match ArtifactStatName::from_yas(s) {
+        Some(v) => v,
+        None => utils::error_and_quit(&format!("未知的属性：{}", s)),
+    });
+
+    let artifacts = match history.artifacts(&query) {
+        Ok(v) => v,
+        Err(e) => utils::error_and_quit(&e),
+    };
+    for a in artifacts.iter() {
+        println!("#{} {}", a.scan_id, a.artifact);
+    }
+}
+
 fn main() {
     Builder::new().filter_level(LevelFilter::Info).init();
     let args: Vec<String> = std::env::args().collect();
 
     let version = get_version();
+    let format_names = ExporterRegistry::default().names();
 
     let matches = App::new("YAS - 原神圣遗物导出器")
         .version(version.as_str())
@@ -283,6 +511,24 @@ fn main() {
                 .takes_value(false)
                 .help("仅扫描20级圣遗物"),
         )
+        .arg(
+            Arg::with_name("stop-key")
+                .long("stop-key")
+                .takes_value(true)
+                .help("终止扫描的按键。rmb：鼠标右键（默认）；mmb：鼠标中键；x1、x2：鼠标侧键；esc；space；f1至f12"),
+        )
+        .arg(
+            Arg::with_name("pause-key")
+                .long("pause-key")
+                .takes_value(true)
+                .help("暂停扫描的按键，再次按下后从暂停的位置继续，可用的按键与--stop-key相同，默认为mmb（鼠标中键）"),
+        )
+        .arg(
+            Arg::with_name("filter")
+                .long("filter")
+                .takes_value(true)
+                .help("只导出满足条件的圣遗物，如\"set in [EmblemOfSeveredFate, ShimenawaReminiscence] and level >= 16 and main != hp\"。可用字段：set、slot、main、sub、star、level，可用and、or、not和括号组合"),
+        )
         .arg(
             Arg::with_name("min-star")
                 .long("min-star")
@@ -342,12 +588,187 @@ fn main() {
                 .long("output-format")
                 .short("f")
                 .takes_value(true)
-                .help("输出格式。mona：莫纳占卜铺（默认）；mingyulab：原魔计算器。")
-                .possible_values(&["mona", "mingyulab"])
-                .default_value("mona"),
+                .use_delimiter(true)
+                .help("输出格式，可用逗号分隔同时导出多种，如mona,good,csv。mona：莫纳占卜铺（默认）；mingyulab：原魔计算器；yas：Yas原生格式，保留全部识别信息；good：GOOD格式（Genshin Optimizer等）；csv/tsv：表格，每行一个圣遗物；html：离线网页报告，包含每个圣遗物的截图、识别置信度和异常提示。扫描武器、角色和材料时只支持good（默认）和yas")
+                .possible_values(&format_names),
+        )
+        .arg(
+            Arg::with_name("mode")
+                .long("mode")
+                .takes_value(true)
+                .possible_values(&["artifact", "weapon", "character", "material"])
+                .default_value("artifact")
+                .help("扫描内容，artifact：圣遗物；weapon：武器，需先打开背包的武器页；character：角色，需先打开角色界面；material：材料，需先打开背包的材料页"),
+        )
+        .arg(
+            Arg::with_name("material-templates")
+                .long("material-templates")
+                .takes_value(true)
+                .help("材料图标模板目录，每个图标以材料的GOOD key命名，如TeachingsOfFreedom.png，默认为material_templates"),
+        )
+        .arg(
+            Arg::with_name("weapon-min-star")
+                .long("weapon-min-star")
+                .takes_value(true)
+                .help("扫描武器时的最小星级，默认为3"),
+        )
+        .arg(
+            Arg::with_name("csv-header")
+                .long("csv-header")
+                .takes_value(true)
+                .help("csv/tsv的列名风格。snake：main_stat_value（默认）；camel：mainStatValue；zh：中文")
+                .possible_values(&["snake", "camel", "zh"])
+                .default_value("snake"),
+        )
+        .arg(
+            Arg::with_name("csv-fraction")
+                .long("csv-fraction")
+                .help("csv/tsv中百分比数值以小数输出（如0.466），默认与游戏内显示一致（如46.6）"),
+        )
+        .arg(
+            Arg::with_name("mona-version")
+                .long("mona-version")
+                .takes_value(true)
+                .help("莫纳占卜铺格式的版本。1：默认；2：额外包含装备角色和圣遗物id（暂不识别锁定状态，不含lock字段）")
+                .possible_values(&MONA_FORMAT_VERSIONS)
+                .default_value("1"),
+        )
+        .arg(
+            Arg::with_name("resume")
+                .long("resume")
+                .help("从输出目录中的断点（yas_checkpoint.json）继续上次未完成的扫描"),
+        )
+        .arg(
+            Arg::with_name("incremental")
+                .long("incremental")
+                .takes_value(true)
+                .help("增量扫描：背包按“最新获得”排序时，扫描到与上次yas格式结果（需带扫描信息）连续一致的圣遗物后停止，其余圣遗物沿用上次结果"),
+        )
+        .arg(
+            Arg::with_name("merge-into")
+                .long("merge-into")
+                .takes_value(true)
+                .help("将扫描结果合并到已有的莫纳占卜铺文件中，保留其中的omit等用户标记，删除扫描范围内已不存在的圣遗物，原文件备份为.bak。扫描中断时不合并"),
+        )
+        .arg(
+            Arg::with_name("db")
+                .long("db")
+                .takes_value(true)
+                .help("将本次扫描结果追加到SQLite数据库（如yas.sqlite），可用history子命令查询"),
+        )
+        .arg(
+            Arg::with_name("account")
+                .long("account")
+                .takes_value(true)
+                .help("账号标记，与扫描结果一同写入数据库"),
+        )
+        .arg(
+            Arg::with_name("stream")
+                .long("stream")
+                .takes_value(true)
+                .help("扫描过程中将每个圣遗物以一行JSON（NDJSON）实时写入指定文件，-表示标准输出"),
+        )
+        .arg(
+            Arg::with_name("save-images")
+                .long("save-images")
+                .takes_value(true)
+                .help("将每个圣遗物的完整截图保存到指定目录，文件名为导出结果中的id"),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about("比较同一账号的两次导出结果")
+                .arg(Arg::with_name("old").required(true).help("旧的导出文件"))
+                .arg(Arg::with_name("new").required(true).help("新的导出文件"))
+                .arg(
+                    Arg::with_name("input-format")
+                        .long("input-format")
+                        .takes_value(true)
+                        .possible_values(&READ_FORMATS)
+                        .help("导出文件的格式，默认根据内容自动识别"),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("以JSON格式输出"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("convert")
+                .about("在不同导出格式之间转换，无法在目标格式中表示的圣遗物会逐个列出")
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .possible_values(&READ_FORMATS)
+                        .help("输入文件的格式，默认根据内容自动识别"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .required(true)
+                        .possible_values(&format_names)
+                        .help("输出格式"),
+                )
+                .arg(Arg::with_name("input").required(true).help("输入文件"))
+                .arg(Arg::with_name("output").required(true).help("输出文件")),
+        )
+        .subcommand(
+            SubCommand::with_name("history")
+                .about("查看数据库中的历次扫描，或按条件查询圣遗物")
+                .arg(
+                    Arg::with_name("db")
+                        .long("db")
+                        .takes_value(true)
+                        .required(true)
+                        .help("SQLite数据库路径"),
+                )
+                .arg(
+                    Arg::with_name("scan")
+                        .long("scan")
+                        .takes_value(true)
+                        .help("只查询指定编号的扫描"),
+                )
+                .arg(
+                    Arg::with_name("account")
+                        .long("account")
+                        .takes_value(true)
+                        .help("只查询指定账号"),
+                )
+                .arg(
+                    Arg::with_name("set")
+                        .long("set")
+                        .takes_value(true)
+                        .help("套装，与yas格式的setName一致，如EmblemOfSeveredFate"),
+                )
+                .arg(
+                    Arg::with_name("slot")
+                        .long("slot")
+                        .takes_value(true)
+                        .help("位置，如Flower、Goblet"),
+                )
+                .arg(
+                    Arg::with_name("main")
+                        .long("main")
+                        .takes_value(true)
+                        .help("主词条，如CriticalDamage"),
+                ),
         )
         .get_matches();
 
+    if let Some(m) = matches.subcommand_matches("diff") {
+        do_diff(m);
+        return;
+    }
+    if let Some(m) = matches.subcommand_matches("convert") {
+        do_convert(m);
+        return;
+    }
+    if let Some(m) = matches.subcommand_matches("history") {
+        do_history(m);
+        return;
+    }
+
     if !utils::is_admin() {
         utils::run_as_admin_exit()
     }
@@ -361,8 +782,9 @@ fn main() {
         nwg::dispatch_thread_events();
     } else {
         let config = YasScannerConfig::from_match(&matches);
+        let options = ExportOptions::from_match(&matches);
 
-        do_scan(config);
+        do_scan(config, options);
         info!("识别结束，请按Enter退出");
         let mut s = String::new();
         stdin().read_line(&mut s).unwrap();
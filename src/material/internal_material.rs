@@ -0,0 +1,15 @@
+use std::fmt;
+
+// One stack of the materials tab
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+pub struct InternalMaterial {
+    // name of the template the icon matched, the same as the GOOD material key
+    pub key: String,
+    pub count: u32,
+}
+
+impl fmt::Display for InternalMaterial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} x{}", self.key, self.count)
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::Path;
+
+use image::imageops::{resize, FilterType};
+use image::RgbImage;
+
+// icons are compared at this size, which hides small offsets of the grid
+const TEMPLATE_SIZE: u32 = 32;
+// mean difference of a channel, above which two icons are different materials
+const MAX_DISTANCE: f64 = 24.0;
+// variance of the brightness, below which a cell holds no item
+const EMPTY_VARIANCE: f64 = 40.0;
+
+pub struct MaterialTemplates {
+    // the key and the downscaled icon
+    templates: Vec<(String, RgbImage)>,
+}
+
+pub fn normalize_icon(icon: &RgbImage) -> RgbImage {
+    resize(icon, TEMPLATE_SIZE, TEMPLATE_SIZE, FilterType::Triangle)
+}
+
+// Both icons have to be normalized
+pub fn icon_distance(a: &RgbImage, b: &RgbImage) -> f64 {
+    let sum: u64 = a.as_raw()
+        .iter()
+        .zip(b.as_raw().iter())
+        .map(|(x, y)| (*x as i32 - *y as i32).abs() as u64)
+        .sum();
+    sum as f64 / a.as_raw().len() as f64
+}
+
+pub fn is_same_icon(a: &RgbImage, b: &RgbImage) -> bool {
+    icon_distance(a, b) < MAX_DISTANCE
+}
+
+// Empty cells after the last stack are filled with a single color
+pub fn is_empty_icon(icon: &RgbImage) -> bool {
+    let brightness: Vec<f64> = icon.pixels()
+        .map(|p| (p[0] as f64 + p[1] as f64 + p[2] as f64) / 3.0)
+        .collect();
+    let n = brightness.len() as f64;
+    let mean = brightness.iter().sum::<f64>() / n;
+    let variance = brightness.iter().map(|b| (b - mean) * (b - mean)).sum::<f64>() / n;
+    variance < EMPTY_VARIANCE
+}
+
+impl MaterialTemplates {
+    // Every png in the directory is a template, named after the GOOD key of the material,
+    // e.g. "TeachingsOfFreedom.png"
+    pub fn load(dir: &str) -> Result<MaterialTemplates, String> {
+        let entries = fs::read_dir(dir).map_err(|e| format!("couldn't read {}: {}", dir, e))?;
+        let mut templates: Vec<(String, RgbImage)> = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("png") {
+                continue;
+            }
+            let key = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(v) => String::from(v),
+                None => continue,
+            };
+            let icon = image::open(&path)
+                .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?
+                .to_rgb8();
+            templates.push((key, normalize_icon(&icon)));
+        }
+        templates.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(MaterialTemplates { templates })
+    }
+
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    // The key of the closest template, if it is close enough. The icon has to be normalized
+    pub fn find(&self, icon: &RgbImage) -> Option<&str> {
+        self.templates
+            .iter()
+            .map(|(key, template)| (key, icon_distance(icon, template)))
+            .filter(|(_, distance)| *distance < MAX_DISTANCE)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(key, _)| key.as_str())
+    }
+}
+
+// Saved icons can be renamed to their keys and moved to the template directory
+pub fn save_icon(icon: &RgbImage, dir: &Path, name: &str) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("couldn't create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}.png", name));
+    icon.save(&path).map_err(|e| format!("couldn't write to {}: {}", path.display(), e))
+}
@@ -0,0 +1,2 @@
+pub mod internal_material;
+pub mod material_template;
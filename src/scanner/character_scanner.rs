@@ -0,0 +1,204 @@
+use enigo::*;
+use log::{error, info, warn};
+
+use crate::capture;
+use crate::character::internal_character::{CharacterName, InternalCharacter};
+use crate::common::{level, utils, PixelRectBound};
+use crate::scanner::unknown_names::UnknownNames;
+use crate::scanner::yas_scanner::YasScanner;
+
+// characters with an alternate sprint listed between their skill and burst
+const ALTERNATE_SPRINT: [&str; 2] = ["KamisatoAyaka", "Mona"];
+// the 3rd and 5th constellations raise a talent by 3
+const CONSTELLATION_TALENT_BONUS: u32 = 3;
+// characters whose 3rd constellation raises the burst and the 5th the skill,
+// for everyone else the 3rd raises the skill and the 5th the burst
+const C3_RAISES_BURST: [&str; 22] = [
+    "Traveler", "Amber", "Barbara", "Chongyun", "Diona", "Eula", "Ganyu", "Jean",
+    "KamisatoAyaka", "Keqing", "KujouSara", "Lisa", "Mona", "Ningguang", "Qiqi", "RaidenShogun",
+    "Razor", "SangonomiyaKokomi", "Venti", "Xiangling", "Xingqiu", "YunJin",
+];
+const MAX_TALENT_LEVEL: u32 = 10;
+// stop walking the roster even if the first character never comes around again
+const MAX_CHARACTERS: u32 = 100;
+// sum of r, g and b above which a constellation is lit
+const CONSTELLATION_LIT: u32 = 450;
+
+#[derive(Debug, Clone)]
+pub struct YasCharacterScanResult {
+    pub name: String,
+    pub level: String,
+    pub constellation: u32,
+    // in the order of the talent tab
+    pub talents: Vec<String>,
+}
+
+// "Lv.10"
+fn parse_talent(s: &str) -> Option<u32> {
+    let level = s.trim_start_matches(|c: char| !c.is_ascii_digit()).trim().parse::<u32>().ok()?;
+    if level < 1 || level > MAX_TALENT_LEVEL + CONSTELLATION_TALENT_BONUS {
+        return None;
+    }
+    Some(level)
+}
+
+// The bonus included in the shown (skill, burst) levels, None for characters not in the tables
+fn constellation_bonus(name: &CharacterName, constellation: u32) -> Option<(u32, u32)> {
+    let key = match name {
+        CharacterName::Known(key) => key,
+        CharacterName::Unknown { .. } => return None,
+    };
+    let bonus = |c: u32| if constellation >= c { CONSTELLATION_TALENT_BONUS } else { 0 };
+    if C3_RAISES_BURST.contains(key) {
+        Some((bonus(5), bonus(3)))
+    } else {
+        Some((bonus(3), bonus(5)))
+    }
+}
+
+// Levels above 10 must include the bonus of a constellation, for characters whose bonus is not known.
+// A raised level up to 10 looks the same as a natural one and is kept as shown
+fn guess_base_talent(shown: u32) -> u32 {
+    if shown > MAX_TALENT_LEVEL {
+        shown - CONSTELLATION_TALENT_BONUS
+    } else {
+        shown
+    }
+}
+
+impl YasCharacterScanResult {
+    pub fn to_internal_character(&self) -> Option<InternalCharacter> {
+        let (level, max_level) = level::parse_level(&self.level)?;
+        let ascension = level::ascension_from_level(level, max_level)?;
+
+        let name = UnknownNames::resolve(&self.name, CharacterName::from_zh_cn(&self.name), |raw_name| {
+            CharacterName::Unknown { raw_name }
+        })?;
+
+        let burst_index = if ALTERNATE_SPRINT.contains(&name.key()) { 3 } else { 2 };
+        let bonus = constellation_bonus(&name, self.constellation);
+        let talent = |i: usize, bonus: Option<u32>| {
+            let shown = self.talents.get(i).and_then(|s| parse_talent(s))?;
+            match bonus {
+                Some(b) => shown.checked_sub(b).filter(|&l| l >= 1),
+                None => Some(guess_base_talent(shown)),
+            }
+        };
+
+        Some(InternalCharacter {
+            level,
+            ascension,
+            constellation: self.constellation,
+            talent_auto: talent(0, bonus.map(|_| 0))?,
+            talent_skill: talent(1, bonus.map(|(skill, _)| skill))?,
+            talent_burst: talent(burst_index, bonus.map(|(_, burst)| burst))?,
+            name,
+        })
+    }
+}
+
+impl YasScanner {
+    fn click_at(&mut self, x: u32, y: u32) {
+        self.enigo.mouse_move_to(self.info.left + x as i32, self.info.top + y as i32);
+        self.enigo.mouse_click(MouseButton::Left);
+    }
+
+    fn recognize_text(&mut self, rect: &PixelRectBound) -> Result<String, String> {
+        let raw = rect.capture_relative(&self.info)?;
+        Ok(self.model.inference_string(&raw))
+    }
+
+    // constellations are activated in order, count the lit ones from the first
+    fn get_constellation(&self) -> u32 {
+        self.info.character_constellation_points
+            .iter()
+            .take_while(|(x, y)| {
+                let color = capture::get_color((*x as i32 + self.info.left) as u32, (*y as i32 + self.info.top) as u32);
+                color.0 as u32 + color.1 as u32 + color.2 as u32 > CONSTELLATION_LIT
+            })
+            .count() as u32
+    }
+
+    fn recognize_character(&mut self) -> Result<YasCharacterScanResult, String> {
+        let info = self.info.clone();
+
+        self.click_at(info.character_tab_x, info.character_attribute_tab_y);
+        utils::sleep(500);
+        let name = self.recognize_text(&info.character_name_position)?;
+        let level = self.recognize_text(&info.character_level_position)?;
+
+        // the constellation screen zooms in before the nodes settle
+        self.click_at(info.character_tab_x, info.character_constellation_tab_y);
+        utils::sleep(1500);
+        let constellation = self.get_constellation();
+
+        self.click_at(info.character_tab_x, info.character_talent_tab_y);
+        utils::sleep(500);
+        let talents = info.character_talent_positions
+            .iter()
+            .map(|rect| self.recognize_text(rect))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(YasCharacterScanResult {
+            name,
+            level,
+            constellation,
+            talents,
+        })
+    }
+
+    // Walks the character screen from the character shown when the scan starts,
+    // until the first character comes around again
+    pub fn start_characters(&mut self) -> Vec<InternalCharacter> {
+        let mut results: Vec<InternalCharacter> = Vec::new();
+        let mut unknown_names = UnknownNames::new("角色", "角色");
+        let mut first_name: Option<String> = None;
+        let mut error_count = 0;
+
+        for _ in 0..MAX_CHARACTERS {
+            // 按下终止键或被取消
+            if self.is_cancelled() {
+                break;
+            }
+
+            let result = match self.recognize_character() {
+                Ok(v) => v,
+                Err(e) => {
+                    error!("截图失败，停止扫描: {}", e);
+                    break;
+                },
+            };
+            if self.config.verbose {
+                info!("{:?}", result);
+            }
+            match first_name {
+                Some(ref name) if *name == result.name => break,
+                None => first_name = Some(result.name.clone()),
+                _ => (),
+            }
+
+            match result.to_internal_character() {
+                Some(character) => {
+                    if let CharacterName::Unknown { ref raw_name } = character.name {
+                        warn!("unknown character: {:?}", result);
+                        unknown_names.add(raw_name);
+                    }
+                    results.push(character);
+                },
+                None => {
+                    error!("wrong detection: {:?}", result);
+                    error_count += 1;
+                },
+            }
+
+            let (x, y) = (self.info.character_next_x, self.info.character_next_y);
+            self.click_at(x, y);
+            utils::sleep(1000);
+        }
+
+        info!("character count: {}", results.len());
+        info!("error count: {}", error_count);
+        unknown_names.report();
+        results
+    }
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::Path;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+
+use crate::expo::yas::{record_from_yas, YasArtifact};
+use crate::scanner::yas_scanner::YasArtifactRecord;
+
+pub const CHECKPOINT_FILE: &str = "yas_checkpoint.json";
+const CHECKPOINT_VERSION: u32 = 1;
+
+// How far a scan got, in the order of the backpack grid
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CheckpointPosition {
+    // artifact count detected when the scan started
+    pub count: u32,
+    pub scanned_count: u32,
+    pub scanned_row: u32,
+}
+
+struct Checkpoint<'a> {
+    position: &'a CheckpointPosition,
+    records: &'a [YasArtifactRecord],
+}
+
+impl<'a> Serialize for Checkpoint<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let artifacts: Vec<YasArtifact> = self.records
+            .iter()
+            .map(|record| YasArtifact { artifact: &record.artifact, record: Some(record) })
+            .collect();
+
+        let mut root = serializer.serialize_map(Some(6))?;
+        root.serialize_entry("format", "yas-checkpoint")?;
+        root.serialize_entry("version", &CHECKPOINT_VERSION)?;
+        root.serialize_entry("count", &self.position.count)?;
+        root.serialize_entry("scannedCount", &self.position.scanned_count)?;
+        root.serialize_entry("scannedRow", &self.position.scanned_row)?;
+        root.serialize_entry("artifacts", &artifacts)?;
+        root.end()
+    }
+}
+
+// Written to a temporary file first, so that a crash while saving keeps the previous checkpoint
+pub fn save_checkpoint(path: &Path, position: &CheckpointPosition, records: &[YasArtifactRecord]) -> Result<(), String> {
+    let s = serde_json::to_string(&Checkpoint { position, records }).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, s).map_err(|e| format!("couldn't write to {}: {}", tmp.display(), e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("couldn't write to {}: {}", path.display(), e))
+}
+
+pub fn load_checkpoint(path: &Path) -> Result<(CheckpointPosition, Vec<YasArtifactRecord>), String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+    let json: Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    if json["format"].as_str() != Some("yas-checkpoint") {
+        return Err(format!("not a yas checkpoint: {}", path.display()));
+    }
+    if json["version"].as_u64() != Some(CHECKPOINT_VERSION as u64) {
+        return Err(String::from("checkpoint was written by another version of yas"));
+    }
+
+    let field = |key: &str| -> Result<u32, String> {
+        json[key].as_u64().map(|v| v as u32).ok_or(format!("missing {}", key))
+    };
+    let position = CheckpointPosition {
+        count: field("count")?,
+        scanned_count: field("scannedCount")?,
+        scanned_row: field("scannedRow")?,
+    };
+
+    let arts = json["artifacts"].as_array().ok_or("missing artifacts")?;
+    let mut records: Vec<YasArtifactRecord> = Vec::new();
+    for art in arts.iter() {
+        records.push(record_from_yas(art)?);
+    }
+
+    Ok((position, records))
+}
@@ -0,0 +1,31 @@
+use std::sync::mpsc::Sender;
+
+use crate::artifact::internal_artifact::InternalArtifact;
+use crate::scanner::yas_scanner::{YasArtifactMeta, YasScanResult};
+
+// Progress of an artifact scan, see `YasScanner::subscribe`.
+// Captures and scrolls come from the scanning thread and recognition results from the recognition thread,
+// so the result of an artifact arrives some time after its capture
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    // the artifact count shown in the backpack
+    ScanStarted { count: u32 },
+    ArtifactCaptured { meta: YasArtifactMeta },
+    // confidence in `meta` is filled from here on
+    ArtifactRecognized { artifact: InternalArtifact, meta: YasArtifactMeta },
+    ParseFailed { meta: YasArtifactMeta, raw: YasScanResult },
+    Duplicate { meta: YasArtifactMeta, raw: YasScanResult },
+    Scrolled { rows: u32 },
+    // the pause key was pressed, the scan goes on from the same cell after `Resumed`
+    Paused,
+    Resumed,
+    // the count includes artifacts kept from a checkpoint or a previous scan
+    Finished { count: usize, interrupted: bool },
+}
+
+// A receiver which is gone does not stop the scan
+pub(crate) fn send_event(events: &Option<Sender<ScanEvent>>, event: ScanEvent) {
+    if let Some(sender) = events {
+        let _ = sender.send(event);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::capture;
+use crate::common::PixelRect;
+use crate::info::info::ScanInfo;
+
+// every n-th pixel of a line is compared, the grid only moves vertically
+const SAMPLE_STEP: u32 = 4;
+// mean difference of gray values, above which two captures don't show the same part of the grid
+const MAX_GRID_DIFF: f64 = 12.0;
+
+// A grayscale capture of the backpack grid, downsampled horizontally
+pub(crate) struct GridImage {
+    width: usize,
+    height: usize,
+    // distance between the tops of two rows
+    pitch: usize,
+    data: Vec<f32>,
+}
+
+impl GridImage {
+    pub(crate) fn capture(info: &ScanInfo, rows: u32, cols: u32) -> Result<GridImage, String> {
+        let rect = PixelRect {
+            left: info.left + info.left_margin as i32,
+            top: info.top + info.top_margin as i32,
+            width: ((info.art_width + info.art_gap_x) * cols - info.art_gap_x) as i32,
+            height: ((info.art_height + info.art_gap_y) * rows - info.art_gap_y) as i32,
+        };
+        let img = capture::capture_absolute_image(&rect)?;
+
+        let width = ((img.width() + SAMPLE_STEP - 1) / SAMPLE_STEP) as usize;
+        let height = img.height() as usize;
+        let mut data: Vec<f32> = Vec::with_capacity(width * height);
+        for y in 0..img.height() {
+            for x in (0..img.width()).step_by(SAMPLE_STEP as usize) {
+                let p = img.get_pixel(x, y);
+                data.push(0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32);
+            }
+        }
+
+        let pitch = (info.art_height + info.art_gap_y) as usize;
+        Ok(GridImage { width, height, pitch, data })
+    }
+
+    fn line(&self, y: usize) -> &[f32] {
+        &self.data[y * self.width..(y + 1) * self.width]
+    }
+
+    // Mean difference of the lines both show when `after` is this grid scrolled down by `offset` pixels,
+    // None once it exceeds `limit`
+    fn diff(&self, after: &GridImage, offset: i32, limit: f64) -> Option<f64> {
+        let overlap = self.height - offset.abs() as usize;
+        let (before_start, after_start) = if offset >= 0 {
+            (offset as usize, 0)
+        } else {
+            (0, (-offset) as usize)
+        };
+
+        let total_limit = limit * (overlap * self.width) as f64;
+        let mut sum = 0.0;
+        for i in 0..overlap {
+            sum += self.line(before_start + i)
+                .iter()
+                .zip(after.line(after_start + i).iter())
+                .map(|(a, b)| (a - b).abs() as f64)
+                .sum::<f64>();
+            if sum > total_limit {
+                return None;
+            }
+        }
+
+        Some(sum / (overlap * self.width) as f64)
+    }
+}
+
+// How many pixels the grid scrolled down from `before` to `after`, negative if it scrolled up.
+// None when no offset up to `max_offset` makes them match, e.g. when a popup covers the grid.
+// Rows look alike, an offset of a whole row could match better than the real one,
+// so offsets are only searched within one row
+pub(crate) fn measure_scroll(before: &GridImage, after: &GridImage, max_offset: usize) -> Option<i32> {
+    let max_offset = max_offset
+        .min(before.pitch.saturating_sub(1))
+        .min(before.height.saturating_sub(1)) as i32;
+    let mut best: Option<(i32, f64)> = None;
+    // small offsets first, they win a tie
+    for offset in (0..=max_offset).flat_map(|i| if i == 0 { vec![0] } else { vec![i, -i] }) {
+        let limit = best.map_or(MAX_GRID_DIFF, |(_, d)| d);
+        if let Some(d) = before.diff(after, offset, limit) {
+            if best.map_or(true, |(_, b)| d < b) {
+                best = Some((offset, d));
+            }
+        }
+    }
+
+    best.map(|(offset, _)| offset)
+}
@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use enigo::*;
+use image::RgbImage;
+use log::{error, info, warn};
+
+use crate::capture;
+use crate::common::{utils, PixelRect, PixelRectBound};
+use crate::material::internal_material::InternalMaterial;
+use crate::material::material_template::{is_empty_icon, is_same_icon, normalize_icon, save_icon, MaterialTemplates};
+use crate::scanner::yas_scanner::{ScrollResult, YasScanner};
+
+// the count is shown on a strip at the bottom of a cell, below the icon
+const COUNT_TOP_RATIO: f64 = 0.8;
+const UNKNOWN_DIR: &str = "unknown_materials";
+
+fn parse_count(s: &str) -> Option<u32> {
+    s.trim().parse::<u32>().ok()
+}
+
+impl YasScanner {
+    fn cell_rect(&self, row: u32, col: u32) -> PixelRect {
+        let info = &self.info;
+        PixelRect {
+            left: info.left + (info.left_margin + (info.art_width + info.art_gap_x) * col) as i32,
+            top: self.row_top(row),
+            width: info.art_width as i32,
+            height: info.art_height as i32,
+        }
+    }
+
+    // The icon and the count of a cell, read from the grid without selecting it
+    fn capture_material(&mut self, row: u32, col: u32) -> Result<(RgbImage, String), String> {
+        let cell = self.cell_rect(row, col);
+        let count_top = cell.top + (cell.height as f64 * COUNT_TOP_RATIO) as i32;
+        let icon = capture::capture_absolute_image(&PixelRect {
+            left: cell.left,
+            top: cell.top,
+            width: cell.width,
+            height: count_top - cell.top,
+        })?;
+        let count_rect = PixelRectBound {
+            left: cell.left,
+            top: count_top,
+            right: cell.left + cell.width,
+            bottom: cell.top + cell.height,
+        };
+        let raw_count = count_rect.capture_absolute()?;
+
+        Ok((icon, self.model.inference_string(&raw_count)))
+    }
+
+    // Scans the opened materials tab of the backpack, which has the same grid as the artifact tab.
+    // Each material is shown once, so the scan ends when a page brings nothing new
+    // or the empty cells after the last stack are reached
+    pub fn start_materials(&mut self) -> Vec<InternalMaterial> {
+        let templates = match MaterialTemplates::load(&self.config.material_templates) {
+            Ok(v) => v,
+            Err(e) => utils::error_and_quit(&e),
+        };
+        info!("material templates: {}", templates.len());
+        if templates.is_empty() {
+            warn!("材料模板目录{}为空，所有材料都将作为未识别图标保存", self.config.material_templates);
+        }
+        let unknown_dir = Path::new(self.config.output_dir.as_deref().unwrap_or(".")).join(UNKNOWN_DIR);
+
+        let mut results: Vec<InternalMaterial> = Vec::new();
+        let mut known: HashSet<String> = HashSet::new();
+        // normalized icons matching no template, kept to skip them when they are shown again
+        let mut unknown_icons: Vec<RgbImage> = Vec::new();
+        let mut error_count = 0;
+        let mut scanned_row = 0_u32;
+
+        self.move_to(0, 0);
+        self.enigo.mouse_click(MouseButton::Left);
+        utils::sleep(1000);
+        self.reset_alignment();
+
+        'outer: loop {
+            let mut new_count = 0;
+            for row in 0..self.row {
+                for col in 0..self.col {
+                    // 按下终止键或被取消
+                    if self.is_cancelled() {
+                        break 'outer;
+                    }
+
+                    // 暂停后从同一个位置继续
+                    if !self.pause_if_requested() {
+                        break 'outer;
+                    }
+
+                    let (icon, raw_count) = match self.capture_material(row, col) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            error!("截图失败，停止扫描: {}", e);
+                            break 'outer;
+                        },
+                    };
+                    if is_empty_icon(&icon) {
+                        break 'outer;
+                    }
+
+                    let normalized = normalize_icon(&icon);
+                    let key = match templates.find(&normalized) {
+                        Some(key) => {
+                            if !known.insert(String::from(key)) {
+                                continue;
+                            }
+                            String::from(key)
+                        },
+                        None => {
+                            if unknown_icons.iter().any(|u| is_same_icon(u, &normalized)) {
+                                continue;
+                            }
+                            unknown_icons.push(normalized);
+                            let name = format!("unknown_{}", unknown_icons.len());
+                            warn!("unknown material, saved as {}", name);
+                            if let Err(e) = save_icon(&icon, &unknown_dir, &name) {
+                                error!("{}", e);
+                            }
+                            new_count += 1;
+                            continue;
+                        },
+                    };
+                    new_count += 1;
+
+                    match parse_count(&raw_count) {
+                        Some(count) => {
+                            let material = InternalMaterial { key, count };
+                            if self.config.verbose {
+                                info!("{}", material);
+                            }
+                            results.push(material);
+                        },
+                        None => {
+                            error!("wrong detection: {} {:?}", key, raw_count);
+                            error_count += 1;
+                        },
+                    }
+                }
+            }
+
+            // scrolling stops at the end of the list, and the page shows the same stacks again
+            if new_count == 0 {
+                break;
+            }
+            scanned_row += self.row;
+            if scanned_row >= self.config.max_row {
+                info!("max row reached, quiting...");
+                break;
+            }
+
+            // a scroll which can't go on ends with the last rows in view, they are scanned once more
+            if let ScrollResult::Interrupt = self.scroll_rows(self.row) {
+                break;
+            }
+            utils::sleep(100);
+        }
+
+        info!("material count: {}", results.len());
+        info!("error count: {}", error_count);
+        if !unknown_icons.is_empty() {
+            warn!(
+                "{}种材料无法识别，图标已保存到{}。将图标重命名为材料的GOOD key（如TeachingsOfFreedom.png）并放入{}后重新扫描",
+                unknown_icons.len(), unknown_dir.display(), self.config.material_templates
+            );
+        }
+        results
+    }
+}
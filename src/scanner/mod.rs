@@ -1 +1,9 @@
-pub mod yas_scanner;
\ No newline at end of file
+pub mod yas_scanner;
+pub mod cancel;
+pub mod checkpoint;
+pub mod event;
+pub mod grid_align;
+pub mod weapon_scanner;
+pub mod character_scanner;
+pub mod material_scanner;
+pub mod unknown_names;
@@ -0,0 +1,50 @@
+use log::warn;
+
+// Sets, weapons and characters released after this version of yas are not in its tables.
+// They keep the recognized name as an Unknown variant, so they still show up in yas output,
+// and the names are listed at the end of the scan to tell the user to update
+pub(crate) struct UnknownNames {
+    // "圣遗物", "武器" or "角色"
+    item: &'static str,
+    // what a new version brings, e.g. "套装"
+    kind: &'static str,
+    names: Vec<String>,
+}
+
+impl UnknownNames {
+    pub(crate) fn new(item: &'static str, kind: &'static str) -> UnknownNames {
+        UnknownNames {
+            item,
+            kind,
+            names: Vec::new(),
+        }
+    }
+
+    // The known name if there is one, otherwise an Unknown keeping `raw`. None for an empty name
+    pub(crate) fn resolve<T>(raw: &str, known: Option<T>, unknown: impl FnOnce(String) -> T) -> Option<T> {
+        match known {
+            Some(v) => Some(v),
+            None if raw.is_empty() => None,
+            None => Some(unknown(String::from(raw))),
+        }
+    }
+
+    pub(crate) fn add(&mut self, name: &str) {
+        if !self.names.iter().any(|n| n == name) {
+            self.names.push(String::from(name));
+        }
+    }
+
+    pub(crate) fn report(&self) {
+        if self.names.is_empty() {
+            return;
+        }
+        warn!(
+            "以下{}名称无法识别，可能是新版本的{}，请更新Yas。这些{}只会出现在yas格式的输出中：",
+            self.item, self.kind, self.item
+        );
+        for name in self.names.iter() {
+            warn!("  {}", name);
+        }
+    }
+}
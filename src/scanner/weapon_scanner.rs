@@ -0,0 +1,183 @@
+use enigo::*;
+use log::{error, info, warn};
+
+use crate::common::{level, utils, PixelRect, PixelRectBound, RawCaptureImage};
+use crate::inference::pre_process::pre_process;
+use crate::scanner::unknown_names::UnknownNames;
+use crate::scanner::yas_scanner::{ScrollResult, YasScanner};
+use crate::weapon::internal_weapon::{InternalWeapon, WeaponName};
+
+#[derive(Debug, Clone)]
+pub struct YasWeaponScanResult {
+    pub name: String,
+    pub level: String,
+    pub refinement: String,
+    pub equip: String,
+    pub star: u32,
+}
+
+impl YasWeaponScanResult {
+    pub fn to_internal_weapon(&self) -> Option<InternalWeapon> {
+        let (level, max_level) = level::parse_level(&self.level)?;
+        let ascension = level::ascension_from_level(level, max_level)?;
+        // "精炼1阶"
+        let refinement = self.refinement.chars().find(|c| c.is_ascii_digit())?.to_digit(10)?;
+        if refinement < 1 || refinement > 5 {
+            return None;
+        }
+
+        let name = UnknownNames::resolve(&self.name, WeaponName::from_zh_cn(&self.name), |raw_title| {
+            WeaponName::Unknown { raw_title }
+        })?;
+
+        let equip = if self.equip.contains("已装备") {
+            Some(String::from(self.equip.split("已装备").next().unwrap()))
+        } else {
+            None
+        };
+
+        Some(InternalWeapon {
+            name,
+            star: self.star,
+            level,
+            ascension,
+            refinement,
+            equip,
+        })
+    }
+}
+
+impl YasScanner {
+    fn recognize_weapon(&mut self, capture: &RawCaptureImage, star: u32) -> YasWeaponScanResult {
+        let info = self.info.clone();
+        let convert_rect = |rect: &PixelRectBound| {
+            PixelRect {
+                left: rect.left - info.panel_position.left,
+                top: rect.top - info.panel_position.top,
+                width: rect.right - rect.left,
+                height: rect.bottom - rect.top,
+            }
+        };
+        let mut model_inference = |pos: &PixelRectBound| {
+            let raw_img = capture.crop_to_raw_img(&convert_rect(pos));
+            self.model.inference_string(&pre_process(raw_img))
+        };
+
+        YasWeaponScanResult {
+            name: model_inference(&info.title_position),
+            level: model_inference(&info.weapon_level_position),
+            refinement: model_inference(&info.weapon_refinement_position),
+            equip: model_inference(&info.equip_position),
+            star,
+        }
+    }
+
+    // Scans the weapon tab of the backpack, which has the same grid as the artifact tab.
+    // Weapons are recognized one by one as there are far fewer of them than artifacts
+    pub fn start_weapons(&mut self) -> Vec<InternalWeapon> {
+        let count = match self.get_item_count("武器") {
+            Ok(v) => v,
+            Err(_) => 2000,
+        };
+
+        let total_row = (count + self.col - 1) / self.col;
+        let last_row_col = if count % self.col == 0 {
+            self.col
+        } else {
+            count % self.col
+        };
+        info!("detected weapon count: {}", count);
+
+        let mut results: Vec<InternalWeapon> = Vec::new();
+        let mut unknown_titles = UnknownNames::new("武器", "武器");
+        let mut error_count = 0;
+
+        let mut scanned_row = 0_u32;
+        let mut scanned_count = 0_u32;
+        let mut start_row = 0_u32;
+
+        self.move_to(0, 0);
+        self.enigo.mouse_click(MouseButton::Left);
+        utils::sleep(1000);
+        self.reset_alignment();
+
+        'outer: while scanned_count < count {
+            for row in start_row..self.row {
+                let c = if scanned_row == total_row - 1 { last_row_col } else { self.col };
+                for col in 0..c {
+                    // 按下终止键或被取消
+                    if self.is_cancelled() {
+                        break 'outer;
+                    }
+
+                    // 暂停后从同一个位置继续
+                    if !self.pause_if_requested() {
+                        break 'outer;
+                    }
+
+                    self.move_to(row, col);
+                    self.enigo.mouse_click(MouseButton::Left);
+                    self.wait_until_switched();
+
+                    let capture = match self.capture_panel() {
+                        Ok(v) => v,
+                        Err(e) => {
+                            error!("截图失败，停止扫描: {}", e);
+                            break 'outer;
+                        },
+                    };
+                    let star = self.get_star();
+                    if star < self.config.weapon_min_star {
+                        break 'outer;
+                    }
+
+                    let result = self.recognize_weapon(&capture, star);
+                    if self.config.verbose {
+                        info!("{:?}", result);
+                    }
+                    match result.to_internal_weapon() {
+                        Some(weapon) => {
+                            if let WeaponName::Unknown { ref raw_title } = weapon.name {
+                                warn!("unknown weapon: {:?}", result);
+                                unknown_titles.add(raw_title);
+                            }
+                            results.push(weapon);
+                        },
+                        None => {
+                            error!("wrong detection: {:?}", result);
+                            error_count += 1;
+                        },
+                    }
+
+                    scanned_count += 1;
+                }
+
+                scanned_row += 1;
+                if scanned_row >= self.config.max_row {
+                    info!("max row reached, quiting...");
+                    break 'outer;
+                }
+            }
+
+            let remain = count - scanned_count;
+            let remain_row = (remain + self.col - 1) / self.col;
+            let scroll_row = remain_row.min(self.row);
+            start_row = self.row - scroll_row;
+            match self.scroll_rows(scroll_row) {
+                ScrollResult::TLE => {
+                    error!("翻页出现问题");
+                    break 'outer;
+                },
+                ScrollResult::Interrupt => break 'outer,
+                _ => (),
+            }
+
+            utils::sleep(100);
+        }
+
+        info!("weapon count: {}", results.len());
+        info!("error count: {}", error_count);
+        unknown_titles.report();
+        results
+    }
+}
@@ -1,10 +1,11 @@
-use std::time::{SystemTime};
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::thread;
 use std::sync::mpsc;
 use std::convert::From;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 // use std::io::stdin;
 use std::fs;
+use std::path::Path;
 use rsevents;
 use rsevents::Awaitable;
 use enigo::*;
@@ -16,23 +17,48 @@ use crate::inference::inference::CRNNModel;
 use crate::common::{utils, RawImage, PixelRect, RawCaptureImage, PixelRectBound};
 use crate::capture;
 use crate::common::color::Color;
-use crate::artifact::internal_artifact::{ArtifactSlot, ArtifactStat, ArtifactSetName, InternalArtifact};
+use crate::artifact::internal_artifact::{correct_artifact_name_chs, ArtifactSlot, ArtifactStat, ArtifactSetName, InternalArtifact};
+use crate::artifact::filter::ArtifactFilter;
 use crate::inference::pre_process::pre_process;
+use crate::expo::stream::YasStream;
+use crate::expo::yas::YasFormat;
+use crate::scanner::checkpoint::{load_checkpoint, save_checkpoint, CheckpointPosition, CHECKPOINT_FILE};
+use crate::scanner::event::{send_event, ScanEvent};
+use crate::scanner::cancel::CancellationToken;
+use crate::scanner::grid_align::{measure_scroll, GridImage};
+use crate::scanner::unknown_names::UnknownNames;
+use image::codecs::jpeg::JpegEncoder;
+use image::ColorType;
 
 pub struct YasScannerConfig {
     pub max_row: u32,
     capture_only: bool,
     pub min_star: u32,
+    pub weapon_min_star: u32,
     max_wait_switch_artifact: u32,
     scroll_stop: u32,
     number: u32,
-    verbose: bool,
+    pub(crate) verbose: bool,
     dump_mode: bool,
     pub offset_x: i32,
     pub offset_y: i32,
     pub output_dir: Option<String>,
     pub format: Option<String>,
-    pub only_level_20:bool
+    // "artifact", "weapon", "character" or "material"
+    pub mode: String,
+    pub material_templates: String,
+    pub only_level_20:bool,
+    // name of the key or mouse button which stops the scan, see `utils::key_code`
+    pub stop_key: String,
+    // pauses before the next artifact, pressed again to go on
+    pub pause_key: String,
+    pub filter: Option<ArtifactFilter>,
+    pub resume: bool,
+    pub incremental: Option<String>,
+    pub stream: Option<String>,
+    // the html export shows the panels, they are only encoded when it is written
+    pub panel_thumbnails: bool,
+    pub save_images: Option<String>,
 }
 
 impl YasScannerConfig {
@@ -42,15 +68,39 @@ impl YasScannerConfig {
             capture_only: matches.is_present("capture-only"),
             dump_mode: matches.is_present("dump"),
             min_star: matches.value_of("min-star").unwrap_or("4").parse::<u32>().unwrap(),
+            weapon_min_star: matches.value_of("weapon-min-star").unwrap_or("3").parse::<u32>().unwrap(),
             max_wait_switch_artifact: matches.value_of("max-wait-switch-artifact").unwrap_or("500").parse::<u32>().unwrap(),
             scroll_stop: matches.value_of("scroll-stop").unwrap_or("80").parse::<u32>().unwrap(),
             number: matches.value_of("number").unwrap_or("0").parse::<u32>().unwrap(),
             verbose: matches.is_present("verbose"),
             output_dir: Some(matches.value_of("output-dir").unwrap_or(".").to_string()),
-            format:Some(matches.value_of("output-format").unwrap_or("mona").to_string()),
+            // several formats are kept comma separated
+            format: Some(matches.values_of("output-format").map(|v| v.collect::<Vec<_>>().join(",")).unwrap_or(String::from(
+                if matches.value_of("mode").unwrap_or("artifact") == "artifact" { "mona" } else { "good" }
+            ))),
+            mode: matches.value_of("mode").unwrap_or("artifact").to_string(),
+            material_templates: matches.value_of("material-templates").unwrap_or("material_templates").to_string(),
             offset_x: matches.value_of("offset-x").unwrap_or("0").parse::<i32>().unwrap(),
             offset_y: matches.value_of("offset-y").unwrap_or("0").parse::<i32>().unwrap(),
             only_level_20: matches.is_present("only20"),
+            stop_key: match matches.value_of("stop-key").unwrap_or("rmb") {
+                s if utils::key_code(s).is_some() => s.to_string(),
+                s => utils::error_and_quit(&format!("未知的按键: {}", s)),
+            },
+            pause_key: match matches.value_of("pause-key").unwrap_or("mmb") {
+                s if s == matches.value_of("stop-key").unwrap_or("rmb") => utils::error_and_quit("暂停键不能与终止键相同"),
+                s if utils::key_code(s).is_some() => s.to_string(),
+                s => utils::error_and_quit(&format!("未知的按键: {}", s)),
+            },
+            filter: matches.value_of("filter").map(|s| match ArtifactFilter::parse(s) {
+                Ok(f) => f,
+                Err(e) => utils::error_and_quit(&format!("无法解析筛选条件: {}", e)),
+            }),
+            resume: matches.is_present("resume"),
+            incremental: matches.value_of("incremental").map(|s| s.to_string()),
+            stream: matches.value_of("stream").map(|s| s.to_string()),
+            save_images: matches.value_of("save-images").map(|s| s.to_string()),
+            panel_thumbnails: matches.values_of("output-format").map_or(false, |mut v| v.any(|f| f == "html")),
         }
     }
 }
@@ -60,6 +110,7 @@ impl Default for YasScannerConfig {
         YasScannerConfig {
             max_row: 1000,
             min_star: 5,
+            weapon_min_star: 3,
             capture_only:false,
             max_wait_switch_artifact: 500,
             scroll_stop: 80,
@@ -70,58 +121,143 @@ impl Default for YasScannerConfig {
             offset_y:0,
             output_dir:Some(".".to_string()),
             format:Some("mona".to_string()),
-            only_level_20:false
+            mode: "artifact".to_string(),
+            material_templates: "material_templates".to_string(),
+            only_level_20:false,
+            stop_key: "rmb".to_string(),
+            pause_key: "mmb".to_string(),
+            filter: None,
+            resume: false,
+            incremental: None,
+            stream: None,
+            panel_thumbnails: false,
+            save_images: None,
         }
     }
 }
 
 pub struct YasScanner {
-    model: CRNNModel,
-    enigo: Enigo,
+    pub(crate) model: CRNNModel,
+    pub(crate) enigo: Enigo,
 
-    info: ScanInfo,
-    config: YasScannerConfig,
+    pub(crate) info: ScanInfo,
+    pub(crate) config: YasScannerConfig,
 
-    row: u32,
-    col: u32,
+    pub(crate) row: u32,
+    pub(crate) col: u32,
 
     pool: f64,
 
-    initial_color: Color,
-
     // for scrolls
-    scrolled_rows: u32,
-    avg_scroll_one_row: f64,
+    measured_scrolls: u32,
+    avg_pixels_per_scroll: f64,
+    // pixels the rows are below where they should be, when whole wheel steps can't align them
+    grid_offset: i32,
 
     avg_switch_time: f64,
     scanned_count: u32,
+
+    events: Option<mpsc::Sender<ScanEvent>>,
+    cancel: CancellationToken,
+    stop_key: i32,
+    pause_key: i32,
+
+    coverage: Option<ScanCoverage>,
 }
 
-enum ScrollResult {
+pub(crate) enum ScrollResult {
     TLE,            // time limit exceeded
     Interrupt,
     Success,
-    Skip,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct YasScanResult {
-    name: String,
-    main_stat_name: String,
-    main_stat_value: String,
-    sub_stat_1: String,
-    sub_stat_2: String,
-    sub_stat_3: String,
-    sub_stat_4: String,
-    level: String,
-    equip: String,
-    star: u32,
+    pub name: String,
+    pub main_stat_name: String,
+    pub main_stat_value: String,
+    pub sub_stat_1: String,
+    pub sub_stat_2: String,
+    pub sub_stat_3: String,
+    pub sub_stat_4: String,
+    pub level: String,
+    pub equip: String,
+    pub star: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct YasArtifactMeta {
+    // order in which the artifact is scanned
+    pub index: u32,
+    // position in the backpack grid, counted from the top
+    pub row: u32,
+    pub col: u32,
+    pub star_detection: String,
+    // unix timestamp in milliseconds
+    pub time: u64,
+    // lowest OCR confidence among all fields, 0 to 1
+    pub confidence: f32,
+}
+
+// The part of the backpack a finished artifact scan went through,
+// artifacts outside of it may still be in the backpack though the scan did not see them
+#[derive(Debug, Clone, Copy)]
+pub struct ScanCoverage {
+    pub min_star: u32,
+    // only artifacts of `min_star` below it are skipped
+    pub min_level: u32,
+    pub only_level_20: bool,
+}
+
+impl ScanCoverage {
+    pub fn contains(&self, artifact: &InternalArtifact) -> bool {
+        artifact.star >= self.min_star
+            && (artifact.star > self.min_star || artifact.level >= self.min_level)
+            && (!self.only_level_20 || artifact.level == 20)
+    }
+}
+
+pub struct YasArtifactRecord {
+    pub artifact: InternalArtifact,
+    pub raw: YasScanResult,
+    pub meta: YasArtifactMeta,
+    // jpeg of the artifact panel, only kept when a report needs it
+    pub panel: Option<Vec<u8>>,
+}
+
+const PANEL_THUMBNAIL_WIDTH: u32 = 360;
+// artifacts recognized between two checkpoints
+const CHECKPOINT_INTERVAL: i32 = 50;
+// artifacts in a row matching the previous scan in the same order, after which an incremental scan stops
+const INCREMENTAL_MATCH_RUN: u32 = 8;
+// rounds of wheel steps tried for one scroll, on top of the ones its distance needs
+const MAX_SCROLL_ROUNDS: u32 = 20;
+// rounds in which the grid does not move before the end of the list is assumed
+const MAX_STUCK_ROUNDS: u32 = 3;
+const MAX_SETTLE_CAPTURES: u32 = 10;
+// captures tried when the grid can't be matched, e.g. because a tooltip covers it
+const MAX_MATCH_RETRIES: u32 = 3;
+// pixels off the row positions which are left alone
+const MIN_ALIGN_ERROR: i32 = 2;
+
+fn panel_thumbnail(capture: &RawCaptureImage) -> Option<Vec<u8>> {
+    let img = capture.to_rgb_image();
+    let w = PANEL_THUMBNAIL_WIDTH.min(img.width());
+    let h = img.height() * w / img.width();
+    let img = image::imageops::thumbnail(&img, w, h);
+
+    let mut buf: Vec<u8> = Vec::new();
+    match JpegEncoder::new_with_quality(&mut buf, 80).encode(&img, w, h, ColorType::Rgb8) {
+        Ok(_) => Some(buf),
+        Err(e) => {
+            warn!("encode panel failed: {}", e);
+            None
+        }
+    }
 }
 
 impl YasScanResult {
     pub fn to_internal_artifact(&self) -> Option<InternalArtifact> {
-        let set_name = ArtifactSetName::from_zh_cn(&self.name)?;
-        let slot = ArtifactSlot::from_zh_cn(&self.name)?;
         let star = self.star;
         if !self.level.contains("+") {
             return None;
@@ -130,13 +266,22 @@ impl YasScanResult {
         let main_stat = ArtifactStat::from_zh_cn_raw(
             (self.main_stat_name.clone() + "+" + self.main_stat_value.as_str()).as_str()
         )?;
+        // a title with one misread character is still recognized
+        let title = correct_artifact_name_chs(&self.name).unwrap_or_else(|| self.name.clone());
+        let set_name = UnknownNames::resolve(&title, ArtifactSetName::from_zh_cn(&title), |raw_title| {
+            ArtifactSetName::Unknown { raw_title }
+        })?;
+        let slot = match set_name {
+            ArtifactSetName::Unknown { .. } => ArtifactSlot::guess(&title, &main_stat.name)?,
+            _ => ArtifactSlot::from_zh_cn(&title)?,
+        };
         let sub1 = ArtifactStat::from_zh_cn_raw(&self.sub_stat_1);
         let sub2 = ArtifactStat::from_zh_cn_raw(&self.sub_stat_2);
         let sub3 = ArtifactStat::from_zh_cn_raw(&self.sub_stat_3);
         let sub4 = ArtifactStat::from_zh_cn_raw(&self.sub_stat_4);
 
         let equip = if self.equip.contains("已装备") {
-            Some(self.equip.chars().take(self.equip.len() - 3).collect::<String>())
+            Some(String::from(self.equip.split("已装备").next().unwrap()))
         } else {
             None
         };
@@ -172,6 +317,8 @@ impl YasScanner {
     pub fn new(info: ScanInfo, config: YasScannerConfig) -> YasScanner {
         let row = info.art_row;
         let col = info.art_col;
+        let stop_key = utils::key_code(&config.stop_key).expect("Unreachable");
+        let pause_key = utils::key_code(&config.pause_key).expect("Unreachable");
 
         YasScanner {
             model: CRNNModel::new(
@@ -186,13 +333,85 @@ impl YasScanner {
             col,
 
             pool: -1.0,
-            initial_color: Color::new(),
-            scrolled_rows: 0,
-            avg_scroll_one_row: 0.0,
+            measured_scrolls: 0,
+            avg_pixels_per_scroll: 0.0,
+            grid_offset: 0,
 
             avg_switch_time: 0.0,
             scanned_count: 0,
+
+            events: None,
+            cancel: CancellationToken::new(),
+            stop_key,
+            pause_key,
+
+            coverage: None,
+        }
+    }
+
+    // Cancelling the token stops the scan as the stop key does, the results so far are kept.
+    // See `CancellationToken::cancel_now` to stop without waiting for the recognition
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    // What the last artifact scan went through, None if it was interrupted or stopped by --max-row
+    pub fn coverage(&self) -> Option<ScanCoverage> {
+        self.coverage
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        if utils::is_key_down(self.stop_key) {
+            self.cancel.cancel();
         }
+        self.cancel.is_cancelled()
+    }
+
+    // Waits while paused, false if the scan is cancelled meanwhile.
+    // The backpack may have been scrolled or clicked during the pause, so the grid is
+    // scrolled back to where it was before going on from the same cell
+    pub(crate) fn pause_if_requested(&mut self) -> bool {
+        if !utils::is_key_down(self.pause_key) {
+            return true;
+        }
+
+        info!("已暂停，按{}继续", self.config.pause_key);
+        send_event(&self.events, ScanEvent::Paused);
+        let reference = match self.capture_grid() {
+            Ok(v) => Some(v),
+            Err(e) => {
+                warn!("{}，继续时不会自动对齐", e);
+                None
+            },
+        };
+        loop {
+            if self.is_cancelled() {
+                return false;
+            }
+            if utils::is_key_down(self.pause_key) {
+                if reference.as_ref().map_or(true, |r| self.align_to(r)) {
+                    break;
+                }
+                warn!("背包未对齐，请将背包滚动回暂停时的位置后再按{}继续", self.config.pause_key);
+            }
+            utils::sleep(100);
+        }
+
+        info!("继续扫描");
+        send_event(&self.events, ScanEvent::Resumed);
+        true
+    }
+
+    // Events of the artifact scans started afterwards are sent to the returned receiver,
+    // which replaces any earlier one
+    pub fn subscribe(&mut self) -> mpsc::Receiver<ScanEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.events = Some(tx);
+        rx
     }
 }
 
@@ -200,23 +419,27 @@ impl YasScanner {
     pub fn move_to(&mut self, row: u32, col: u32) {
         let info = &self.info;
         let left = info.left + (info.left_margin + (info.art_width + info.art_gap_x) * col + info.art_width / 2) as i32;
-        let top = info.top + (info.top_margin + (info.art_height + info.art_gap_y) * row + info.art_height / 4) as i32;
+        let top = self.row_top(row) + (info.art_height / 4) as i32;
         self.enigo.mouse_move_to(left as i32, top as i32);
     }
 
-    fn sample_initial_color(&mut self) {
-        self.initial_color = self.get_color();
+    // Screen position of the top of a visible row
+    pub(crate) fn row_top(&self, row: u32) -> i32 {
+        let info = &self.info;
+        info.top + (info.top_margin + (info.art_height + info.art_gap_y) * row) as i32 + self.grid_offset
     }
 
-    fn get_color(&self) -> Color {
-        let flag_x = self.info.flag_x as i32 + self.info.left;
-        let flag_y = self.info.flag_y as i32 + self.info.top;
-        let color = capture::get_color(flag_x as u32, flag_y as u32);
-
-        color
+    // The scan starts from the top of the backpack, where the rows are in place
+    pub(crate) fn reset_alignment(&mut self) {
+        self.grid_offset = 0;
     }
 
     fn get_art_count(&mut self) -> Result<u32, String> {
+        self.get_item_count("圣遗物")
+    }
+
+    // The count shown at the top of a backpack tab, e.g. "圣遗物 123/1500"
+    pub(crate) fn get_item_count(&mut self, tab: &str) -> Result<u32, String> {
         let count = self.config.number;
         if let 0 = count {
             let info = &self.info;
@@ -224,91 +447,157 @@ impl YasScanner {
             // raw_after_pp.to_gray_image().save("count.png");
             let s = self.model.inference_string(&raw_after_pp);
             info!("raw count string: {}", s);
-            if s.starts_with("圣遗物") {
+            if s.starts_with(tab) {
                 let chars = s.chars().collect::<Vec<char>>();
-                let count_str = (&chars[4..chars.len() - 5]).iter().collect::<String>();
+                let start = tab.chars().count() + 1;
+                if chars.len() < start + 5 {
+                    return Err(format!("无法识别{}数量", tab));
+                }
+                let count_str = (&chars[start..chars.len() - 5]).iter().collect::<String>();
                 let count = match count_str.parse::<u32>() {
                     Ok(v) => v,
                     Err(_) => {
-                        return Err(String::from("无法识别圣遗物数量"));
+                        return Err(format!("无法识别{}数量", tab));
                     }
                 };
                 return Ok(count);
             }
-            Err(String::from("无法识别圣遗物数量"))
+            Err(format!("无法识别{}数量", tab))
         } else {
             return Ok(count);
         }
     }
 
-    fn scroll_one_row(&mut self) -> ScrollResult {
-        let mut state = 0;
-        let mut count = 0;
-        let max_scroll = 20;
-        while count < max_scroll {
-            if utils::is_rmb_down() {
-                return ScrollResult::Interrupt;
-            }
+    fn capture_grid(&self) -> Result<GridImage, String> {
+        GridImage::capture(&self.info, self.row, self.col)
+    }
 
-            self.enigo.mouse_scroll_y(-5);
+    // Captures the grid once it stops moving
+    fn capture_still_grid(&mut self) -> Result<GridImage, String> {
+        let pitch = (self.info.art_height + self.info.art_gap_y) as usize;
+        let mut last = self.capture_grid()?;
+        for _ in 0..MAX_SETTLE_CAPTURES {
             utils::sleep(self.config.scroll_stop);
-            count += 1;
-            let color: Color = self.get_color();
-            // println!("{:?}", color);
-            if state == 0 && !color.is_same(&self.initial_color) {
-                state = 1;
-            } else if state == 1 && self.initial_color.is_same(&color) {
-                self.avg_scroll_one_row = (self.avg_scroll_one_row * self.scrolled_rows as f64 + count as f64) / (self.scrolled_rows as f64 + 1.0);
-                info!("avg scroll/row: {}", self.avg_scroll_one_row);
-                self.scrolled_rows += 1;
-                return ScrollResult::Success;
+            let grid = self.capture_grid()?;
+            if measure_scroll(&last, &grid, pitch / 2) == Some(0) {
+                return Ok(grid);
             }
+            last = grid;
         }
-
-        ScrollResult::TLE
+        Ok(last)
     }
 
-    fn scroll_rows(&mut self, count: u32) -> ScrollResult {
-        if self.scrolled_rows >= 5 {
-            let scroll = ((self.avg_scroll_one_row * count as f64 - 3.0).round() as u32).max(0);
-            for _ in 0..scroll {
-                self.enigo.mouse_scroll_y(-1);
+    // How far the grid moved since `before`. A tooltip or a scroll still going on can spoil a capture,
+    // so a few fresh ones are tried before giving up
+    fn measure_from(&mut self, before: &GridImage) -> Result<(GridImage, i32), String> {
+        let pitch = (self.info.art_height + self.info.art_gap_y) as usize;
+        for _ in 0..MAX_MATCH_RETRIES {
+            let after = self.capture_still_grid()?;
+            if let Some(moved) = measure_scroll(before, &after, pitch - 1) {
+                return Ok((after, moved));
             }
-            utils::sleep(400);
-            self.align_row();
-            return ScrollResult::Skip;
+            utils::sleep(self.config.scroll_stop);
         }
+        Err(String::from("couldn't match the grid before and after scrolling"))
+    }
+
+    // Scrolls the rows up by `pixels`, down when negative. The distance of every round of wheel steps
+    // is measured by matching the grid before and after it, so the steps are corrected as they go.
+    // What whole steps can't reach is kept in `grid_offset`
+    fn scroll_pixels(&mut self, pixels: i32) -> ScrollResult {
+        let pitch = (self.info.art_height + self.info.art_gap_y) as i32;
+        let mut remaining = pixels;
+        let mut stuck = 0;
+        let mut before = match self.capture_still_grid() {
+            Ok(v) => v,
+            Err(e) => {
+                error!("{}", e);
+                return ScrollResult::TLE;
+            },
+        };
+        // each round moves less than a row to be measured, see measure_scroll
+        let max_rounds = (pixels.abs() / (pitch / 2).max(1)) as u32 + MAX_SCROLL_ROUNDS;
 
-        for _ in 0..count {
-            match self.scroll_one_row() {
-                ScrollResult::TLE => return ScrollResult::TLE,
-                ScrollResult::Interrupt => return ScrollResult::Interrupt,
-                _ => (),
+        for _ in 0..max_rounds {
+            let tolerance = ((self.avg_pixels_per_scroll / 2.0) as i32).max(MIN_ALIGN_ERROR);
+            if remaining.abs() <= tolerance {
+                self.grid_offset = remaining;
+                return ScrollResult::Success;
+            }
+            if self.is_cancelled() {
+                return ScrollResult::Interrupt;
             }
-        }
 
-        ScrollResult::Success
-    }
+            // at most half a row per round, an overshoot still stays within a row
+            let target = remaining.abs().min(pitch / 2);
+            let steps = if self.measured_scrolls > 0 {
+                ((target as f64 / self.avg_pixels_per_scroll).round() as i32).max(1)
+            } else {
+                1
+            };
+            let direction = if remaining > 0 { -1 } else { 1 };
+            for _ in 0..steps {
+                self.enigo.mouse_scroll_y(direction);
+            }
 
-    fn align_row(&mut self) -> bool {
-        let mut count = 0;
-        while count < 10 {
-            let color = self.get_color();
-            if color.is_same(&self.initial_color) {
-                return true;
+            let moved = match self.measure_from(&before) {
+                Ok((after, moved)) => {
+                    before = after;
+                    moved
+                },
+                Err(e) => {
+                    warn!("{}", e);
+                    return ScrollResult::TLE;
+                },
+            };
+
+            // the end of the list does not move
+            if moved == 0 {
+                stuck += 1;
+                if stuck >= MAX_STUCK_ROUNDS {
+                    // the rows are in place up to whole rows
+                    self.grid_offset = (remaining % pitch + pitch + pitch / 2) % pitch - pitch / 2;
+                    return ScrollResult::TLE;
+                }
+                continue;
             }
+            stuck = 0;
 
-            self.enigo.mouse_scroll_y(-1);
-            utils::sleep(self.config.scroll_stop);
-            count += 1;
+            let per_step = moved.abs() as f64 / steps as f64;
+            self.avg_pixels_per_scroll = (self.avg_pixels_per_scroll * self.measured_scrolls as f64 + per_step) / (self.measured_scrolls as f64 + 1.0);
+            self.measured_scrolls += 1;
+            remaining -= moved;
+            debug!("scrolled {}px in {} steps, {}px remaining, avg {}px/step", moved, steps, remaining, self.avg_pixels_per_scroll);
         }
 
-        false
+        self.grid_offset = remaining;
+        ScrollResult::TLE
+    }
+
+    pub(crate) fn scroll_rows(&mut self, count: u32) -> ScrollResult {
+        let pitch = (self.info.art_height + self.info.art_gap_y) as i32;
+        self.scroll_pixels(count as i32 * pitch + self.grid_offset)
+    }
+
+    // Scrolls back to where the grid was when `reference` was captured
+    fn align_to(&mut self, reference: &GridImage) -> bool {
+        let moved = match self.measure_from(reference) {
+            Ok((_, moved)) => moved,
+            Err(_) => return false,
+        };
+
+        match self.scroll_pixels(self.grid_offset - moved) {
+            ScrollResult::Success => true,
+            _ => false,
+        }
     }
 
-    fn wait_until_switched(&mut self) -> bool {
+    pub(crate) fn wait_until_switched(&mut self) -> bool {
         let now = SystemTime::now();
         while now.elapsed().unwrap().as_millis() < self.config.max_wait_switch_artifact as u128 {
+            if self.is_cancelled() {
+                return false;
+            }
             // let pool_start = SystemTime::now();
             let rect = PixelRect {
                 left: self.info.left as i32 + self.info.pool_position.left,
@@ -338,7 +627,7 @@ impl YasScanner {
         false
     }
 
-    fn capture_panel(&mut self) -> Result<RawCaptureImage, String> {
+    pub(crate) fn capture_panel(&mut self) -> Result<RawCaptureImage, String> {
         let now = SystemTime::now();
         let w = self.info.panel_position.right - self.info.panel_position.left;
         let h = self.info.panel_position.bottom - self.info.panel_position.top;
@@ -357,7 +646,7 @@ impl YasScanner {
         })
     }
 
-    fn get_star(&self) -> u32 {
+    pub(crate) fn get_star(&self) -> u32 {
         let color = capture::get_color(
             (self.info.star_x as i32 + self.info.left) as u32,
             (self.info.star_y as i32 + self.info.top) as u32
@@ -425,6 +714,15 @@ impl YasScanner {
     }
 
     pub fn start(&mut self) -> Vec<InternalArtifact> {
+        self.start_with_records()
+            .into_iter()
+            .map(|record| record.artifact)
+            .collect()
+    }
+
+    // Like `start`, but keeps the raw recognition results and scan position of every artifact
+    pub fn start_with_records(&mut self) -> Vec<YasArtifactRecord> {
+        self.coverage = None;
         if self.config.capture_only {
             self.start_capture_only();
             return Vec::new();
@@ -453,24 +751,85 @@ impl YasScanner {
         info!("total row: {}", total_row);
         info!("last column: {}", last_row_col);
 
-        let (tx, rx) = mpsc::channel::<Option<(RawCaptureImage, u32)>>();
+        let checkpoint_path = Path::new(self.config.output_dir.as_deref().unwrap_or(".")).join(CHECKPOINT_FILE);
+        let (mut position, resumed) = if self.config.resume {
+            match load_checkpoint(&checkpoint_path) {
+                Ok(v) => v,
+                Err(e) => utils::error_and_quit(&format!("无法读取断点 {}", e)),
+            }
+        } else {
+            (CheckpointPosition::default(), Vec::new())
+        };
+        if self.config.resume {
+            info!("从第{}个圣遗物继续扫描，已识别{}个", position.scanned_count + 1, resumed.len());
+            if position.count != count {
+                warn!("圣遗物数量与断点时不同（{} -> {}），背包有变化时继续扫描的结果可能不正确", position.count, count);
+            }
+        }
+        position.count = count;
+        let resume_from = position.scanned_count;
+        send_event(&self.events, ScanEvent::ScanStarted { count });
+
+        let (tx, rx) = mpsc::channel::<Option<(RawCaptureImage, u32, YasArtifactMeta)>>();
         let info_2 = self.info.clone();
         // v bvvmnvbm
         let is_verbose = self.config.verbose;
         let is_dump_mode = self.config.dump_mode;
         let only_level_20 = self.config.only_level_20;
-        let level20_signal = std::sync::Arc::new(rsevents::ManualResetEvent::new(rsevents::State::Unset));
-        let shared_event = level20_signal.clone();
+        // The backpack is sorted by star and then by level, both descending.
+        // Once an artifact of the lowest wanted star is below the wanted level, no later one matches the filter
+        let min_star = self.config.filter.as_ref()
+            .and_then(|f| f.min_star())
+            .map_or(self.config.min_star, |s| s.max(self.config.min_star));
+        let min_level = self.config.filter.as_ref().and_then(|f| f.min_level());
+        let panel_thumbnails = self.config.panel_thumbnails;
+        let save_images = self.config.save_images.clone();
+        if let Some(ref dir) = save_images {
+            if let Err(e) = fs::create_dir_all(dir) {
+                utils::error_and_quit(&format!("无法创建截图目录 {}: {}", dir, e));
+            }
+        }
+        let previous: Vec<YasArtifactRecord> = match self.config.incremental {
+            Some(ref path) => match fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|s| YasFormat::parse_records(&s)) {
+                Ok(v) => v,
+                Err(e) => utils::error_and_quit(&format!("无法读取上次扫描的yas格式结果 {}: {}", path, e)),
+            },
+            None => Vec::new(),
+        };
+        // set by the recognition thread when the rest of the backpack does not need to be scanned
+        let stop_signal = std::sync::Arc::new(rsevents::ManualResetEvent::new(rsevents::State::Unset));
+        let shared_event = stop_signal.clone();
+        let mut stream = match self.config.stream {
+            Some(ref path) => match YasStream::open(path) {
+                Ok(s) => Some(s),
+                Err(e) => utils::error_and_quit(&e),
+            },
+            None => None,
+        };
+        let checkpoint_path_2 = checkpoint_path.clone();
+        let events_2 = self.events.clone();
+        let cancel_2 = self.cancel.clone();
         let handle = thread::spawn(move || {
-            let mut results: Vec<InternalArtifact> = Vec::new();
+            let mut hash: HashSet<InternalArtifact> = resumed.iter().map(|r| r.artifact.clone()).collect();
+            let mut results: Vec<YasArtifactRecord> = resumed;
             let mut model = CRNNModel::new(
                 String::from("model_training.onnx"),
                 String::from("index_2_word.json")
             );
             let mut error_count = 0;
             let mut dup_count = 0;
-            let mut hash = HashSet::new();
             let mut consecutive_dup_count = 0;
+            // by id, an artifact moved to another character since the previous scan still matches
+            let mut previous_index: HashMap<String, usize> = HashMap::new();
+            for (i, record) in previous.iter().enumerate() {
+                previous_index.entry(record.artifact.id()).or_insert(i);
+            }
+            let mut matched_run = 0;
+            let mut last_matched = 0_usize;
+            // (index in previous, index in this scan) where the scan stopped
+            let mut matched_at: Option<(usize, u32)> = None;
+            let mut unknown_titles = UnknownNames::new("圣遗物", "套装");
+            let mut page_error = false;
             let info = info_2;
 
             let mut cnt = 0;
@@ -488,13 +847,26 @@ impl YasScanner {
             };
 
             for i in rx {
-                let (capture, star) = match i {
+                let (capture, star, mut meta) = match i {
                     Some(v) => v,
                     None => break,
                 };
+                // on `cancel_now` the captures still queued are scanned again when resuming
+                if cancel_2.is_aborted() {
+                    break;
+                }
+                // saved before this artifact is counted, the position matches the results
+                if cnt > 0 && cnt % CHECKPOINT_INTERVAL == 0 {
+                    if let Err(e) = save_checkpoint(&checkpoint_path_2, &position, &results) {
+                        warn!("couldn't save checkpoint: {}", e);
+                    }
+                }
+                position.scanned_count = meta.index + 1;
+                position.scanned_row = meta.row;
                 // let now = SystemTime::now();
 
-                let model_inference = |pos: &PixelRectBound, name: &str, cnt: i32| {
+                let mut confidence: f32 = 1.0;
+                let mut model_inference = |pos: &PixelRectBound, name: &str, cnt: i32| {
                     let raw_img = capture.crop_to_raw_img(&convert_rect(pos));
                     if is_dump_mode {
                         raw_img.grayscale_to_gray_image().save(format!("dumps/{}_{}.png", name, cnt)).expect("Err");
@@ -505,7 +877,8 @@ impl YasScanner {
                         processed_img.to_gray_image().save(format!("dumps/p_{}_{}.png", name, cnt)).expect("Err");
                     }
                     
-                    let inference_result = model.inference_string(&processed_img);
+                    let (inference_result, c) = model.inference_string_with_confidence(&processed_img);
+                    confidence = confidence.min(c);
                     if is_dump_mode {
                         fs::write(format!("dumps/{}_{}.txt", name, cnt), &inference_result).expect("Err");
                     }
@@ -526,6 +899,7 @@ impl YasScanner {
                 let str_equip = model_inference(&info.equip_position, "equip", cnt);
 
                 cnt += 1;
+                meta.confidence = confidence;
 
                 // let predict_time = now.elapsed().unwrap().as_millis();
                 // println!("predict time: {}ms", predict_time);                
@@ -551,43 +925,122 @@ impl YasScanner {
                     if hash.contains(&a) {
                         dup_count += 1;
                         consecutive_dup_count += 1;
+                        matched_run = 0;
                         warn!("dup artifact detected: {:?}", result);
+                        if let Some(s) = stream.as_mut() {
+                            s.duplicate(&meta, &result);
+                        }
+                        send_event(&events_2, ScanEvent::Duplicate { meta, raw: result });
                     } else if a.level<20 && only_level_20 {
                         warn!("low level artifact detected: {:?}", result);
                         shared_event.set();
                         break;
+                    } else if a.star == min_star && min_level.map_or(false, |l| a.level < l) {
+                        info!("之后的圣遗物都不满足筛选条件，停止扫描");
+                        shared_event.set();
+                        break;
                     }
                     else {
                         consecutive_dup_count = 0;
+                        if let ArtifactSetName::Unknown { raw_title } = &a.set_name {
+                            warn!("unknown artifact set: {:?}", result);
+                            unknown_titles.add(raw_title);
+                        }
+                        if let Some(ref dir) = save_images {
+                            let path = Path::new(dir).join(format!("{}.png", a.id()));
+                            if let Err(e) = capture.to_rgb_image().save(&path) {
+                                warn!("couldn't save {}: {}", path.display(), e);
+                            }
+                        }
+                        match previous_index.get(&a.id()) {
+                            Some(&i) => {
+                                matched_run = if matched_run > 0 && i == last_matched + 1 { matched_run + 1 } else { 1 };
+                                last_matched = i;
+                            },
+                            None => matched_run = 0,
+                        }
+                        let index = meta.index;
                         hash.insert(a.clone());
-                        results.push(a);
+                        let record = YasArtifactRecord {
+                            artifact: a,
+                            raw: result,
+                            meta,
+                            panel: if panel_thumbnails { panel_thumbnail(&capture) } else { None },
+                        };
+                        if let Some(s) = stream.as_mut() {
+                            s.artifact(&record);
+                        }
+                        send_event(&events_2, ScanEvent::ArtifactRecognized {
+                            artifact: record.artifact.clone(),
+                            meta: record.meta.clone(),
+                        });
+                        results.push(record);
+                        if matched_run >= INCREMENTAL_MATCH_RUN {
+                            info!("连续{}个圣遗物与上次扫描一致，停止扫描", matched_run);
+                            matched_at = Some((last_matched, index));
+                            shared_event.set();
+                            break;
+                        }
                     }
                 } else {
                     error!("wrong detection: {:?}", result);
+                    if let Some(s) = stream.as_mut() {
+                        s.error(&meta, &result);
+                    }
+                    send_event(&events_2, ScanEvent::ParseFailed { meta, raw: result });
                     error_count += 1;
+                    matched_run = 0;
                     // println!("error parsing results");
                 }
                 if consecutive_dup_count >= info.art_row {
                     error!("检测到连续多个重复圣遗物，可能为翻页错误，或者为非背包顶部开始扫描");
+                    page_error = true;
                     break;
                 }
             }
 
+            if let Some(s) = stream.as_mut() {
+                s.end(results.len(), error_count, dup_count);
+            }
+            if let Err(e) = save_checkpoint(&checkpoint_path_2, &position, &results) {
+                warn!("couldn't save checkpoint: {}", e);
+            }
+            // the artifacts after the matched run are taken from the previous scan, moved to where they are now
+            if let Some((matched, index)) = matched_at {
+                let old_index = previous[matched].meta.index;
+                let mut ids: HashSet<String> = results.iter().map(|r| r.artifact.id()).collect();
+                let mut kept = 0;
+                for mut record in previous.into_iter().skip(matched + 1) {
+                    if !ids.insert(record.artifact.id()) {
+                        continue;
+                    }
+                    record.meta.index = (record.meta.index + index).saturating_sub(old_index);
+                    record.meta.row = record.meta.index / info.art_col;
+                    record.meta.col = record.meta.index % info.art_col;
+                    results.push(record);
+                    kept += 1;
+                }
+                info!("incremental: {} kept from previous scan", kept);
+            }
             info!("error count: {}", error_count);
             info!("dup count: {}", dup_count);
+            unknown_titles.report();
 
-            results
+            (results, page_error)
         });
 
 
         let mut scanned_row = 0_u32;
         let mut scanned_count = 0_u32;
         let mut start_row = 0_u32;
+        // the checkpoint is kept only when the scan did not finish
+        let mut interrupted = false;
+        let mut max_row_reached = false;
 
         self.move_to(0, 0);
         self.enigo.mouse_click(MouseButton::Left);
         utils::sleep(1000);
-        self.sample_initial_color();
+        self.reset_alignment();
 
         'outer: while scanned_count < count {
             'row: for row in start_row..self.row {
@@ -598,15 +1051,28 @@ impl YasScanner {
                         break 'outer;
                     }
 
-                    // 右键终止
-                    if utils::is_rmb_down() {
+                    // 按下终止键或被取消
+                    if self.is_cancelled() {
+                        interrupted = true;
+                        break 'outer;
+                    }
+
+                    // 暂停后从同一个位置继续
+                    if !self.pause_if_requested() {
+                        interrupted = true;
                         break 'outer;
                     }
 
-                    if only_level_20 && level20_signal.wait0() {
+                    if stop_signal.wait0() {
                         break 'outer;
                     }
 
+                    // already recognized before the checkpoint
+                    if scanned_count < resume_from {
+                        scanned_count += 1;
+                        continue 'col;
+                    }
+
                     self.move_to(row, col);
                     self.enigo.mouse_click(MouseButton::Left);
 
@@ -614,10 +1080,20 @@ impl YasScanner {
 
                     let capture = self.capture_panel().unwrap();
                     let star = self.get_star();
-                    if star < self.config.min_star {
+                    if star < min_star {
                         break 'outer;
                     }
-                    match tx.send(Some((capture, star))) {
+                    let meta = YasArtifactMeta {
+                        index: scanned_count,
+                        row: scanned_row,
+                        col,
+                        star_detection: String::from("color"),
+                        time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+                        // filled by the recognition thread
+                        confidence: 0.0,
+                    };
+                    send_event(&self.events, ScanEvent::ArtifactCaptured { meta: meta.clone() });
+                    match tx.send(Some((capture, star, meta))) {
                         Ok(_) => (),
                         Err(_) => ()
                     };
@@ -629,6 +1105,7 @@ impl YasScanner {
 
                 if scanned_row >= self.config.max_row {
                     info!("max row reached, quiting...");
+                    max_row_reached = true;
                     break 'outer;
                 }
             } // end 'row
@@ -640,10 +1117,14 @@ impl YasScanner {
             match self.scroll_rows(scroll_row) {
                 ScrollResult::TLE => {
                     error!("翻页出现问题");
+                    interrupted = true;
                     break 'outer;
                 },
-                ScrollResult::Interrupt => break 'outer,
-                _ => (),
+                ScrollResult::Interrupt => {
+                    interrupted = true;
+                    break 'outer;
+                },
+                _ => send_event(&self.events, ScanEvent::Scrolled { rows: scroll_row }),
             }
 
             utils::sleep(100);
@@ -656,8 +1137,21 @@ impl YasScanner {
         };
 
         info!("扫描结束，等待识别线程结束，请勿关闭程序");
-        let results: Vec<InternalArtifact> = handle.join().unwrap();
+        let (results, page_error) = handle.join().unwrap();
         info!("count: {}", results.len());
+        if !interrupted && !max_row_reached && !page_error {
+            self.coverage = Some(ScanCoverage {
+                min_star,
+                min_level: min_level.unwrap_or(0),
+                only_level_20,
+            });
+        }
+        send_event(&self.events, ScanEvent::Finished { count: results.len(), interrupted });
+        if interrupted {
+            info!("扫描未完成，进度已保存到 {}，可使用 --resume 继续", checkpoint_path.display());
+        } else if let Err(e) = fs::remove_file(&checkpoint_path) {
+            warn!("couldn't remove {}: {}", checkpoint_path.display(), e);
+        }
         results
     }
 }
@@ -0,0 +1,192 @@
+use std::fmt;
+
+use crate::common::level;
+
+// Chinese names and GOOD keys of the weapons known to this version of yas
+const WEAPONS: [(&str, &str); 126] = [
+    // sword
+    ("无锋剑", "DullBlade"),
+    ("银剑", "SilverSword"),
+    ("冷刃", "CoolSteel"),
+    ("黎明神剑", "HarbingerOfDawn"),
+    ("旅行剑", "TravelersHandySword"),
+    ("暗铁剑", "DarkIronSword"),
+    ("飞天御剑", "SkyriderSword"),
+    ("西风剑", "FavoniusSword"),
+    ("笛剑", "TheFlute"),
+    ("祭礼剑", "SacrificialSword"),
+    ("宗室长剑", "RoyalLongsword"),
+    ("匣里龙吟", "LionsRoar"),
+    ("试作斩岩", "PrototypeRancour"),
+    ("铁蜂刺", "IronSting"),
+    ("黑岩长剑", "BlackcliffLongsword"),
+    ("黑剑", "TheBlackSword"),
+    ("暗巷闪光", "TheAlleyFlash"),
+    ("降临之剑", "SwordOfDescension"),
+    ("腐殖之剑", "FesteringDesire"),
+    ("天目影打刀", "AmenomaKageuchi"),
+    ("辰砂之纺锤", "CinnabarSpindle"),
+    ("风鹰剑", "AquilaFavonia"),
+    ("天空之刃", "SkywardBlade"),
+    ("斫峰之刃", "SummitShaper"),
+    ("磐岩结绿", "PrimordialJadeCutter"),
+    ("苍古自由之誓", "FreedomSworn"),
+    ("雾切之回光", "MistsplitterReforged"),
+    // claymore
+    ("训练大剑", "WasterGreatsword"),
+    ("佣兵重剑", "OldMercsPal"),
+    ("铁影阔剑", "FerrousShadow"),
+    ("沐浴龙血的剑", "BloodtaintedGreatsword"),
+    ("以理服人", "DebateClub"),
+    ("白铁大剑", "WhiteIronGreatsword"),
+    ("飞天大御剑", "SkyriderGreatsword"),
+    ("西风大剑", "FavoniusGreatsword"),
+    ("钟剑", "TheBell"),
+    ("祭礼大剑", "SacrificialGreatsword"),
+    ("宗室大剑", "RoyalGreatsword"),
+    ("雨裁", "Rainslasher"),
+    ("试作古华", "PrototypeArchaic"),
+    ("白影剑", "Whiteblind"),
+    ("黑岩斩刀", "BlackcliffSlasher"),
+    ("螭骨剑", "SerpentSpine"),
+    ("千岩古剑", "LithicBlade"),
+    ("雪葬的星银", "SnowTombedStarsilver"),
+    ("衔珠海皇", "LuxuriousSeaLord"),
+    ("恶王丸", "Akuoumaru"),
+    ("桂木斩长正", "KatsuragikiriNagamasa"),
+    ("天空之傲", "SkywardPride"),
+    ("狼的末路", "WolfsGravestone"),
+    ("无工之剑", "TheUnforged"),
+    ("松籁响起之时", "SongOfBrokenPines"),
+    ("赤角石溃杵", "RedhornStonethresher"),
+    // polearm
+    ("新手长枪", "BeginnersProtector"),
+    ("铁尖枪", "IronPoint"),
+    ("白缨枪", "WhiteTassel"),
+    ("钺矛", "Halberd"),
+    ("黑缨枪", "BlackTassel"),
+    ("西风长枪", "FavoniusLance"),
+    ("匣里灭辰", "DragonsBane"),
+    ("试作星镰", "PrototypeStarglitter"),
+    ("流月针", "CrescentPike"),
+    ("黑岩刺枪", "BlackcliffPole"),
+    ("决斗之枪", "Deathmatch"),
+    ("千岩长枪", "LithicSpear"),
+    ("龙脊长枪", "DragonspineSpear"),
+    ("喜多院十文字", "KitainCrossSpear"),
+    ("渔获", "TheCatch"),
+    ("和璞鸢", "PrimordialJadeWingedSpear"),
+    ("天空之脊", "SkywardSpine"),
+    ("贯虹之槊", "VortexVanquisher"),
+    ("护摩之杖", "StaffOfHoma"),
+    ("薙草之稻光", "EngulfingLightning"),
+    ("息灾", "CalamityQueller"),
+    // catalyst
+    ("学徒笔记", "ApprenticesNotes"),
+    ("口袋魔导书", "PocketGrimoire"),
+    ("魔导绪论", "MagicGuide"),
+    ("讨龙英杰谭", "ThrillingTalesOfDragonSlayers"),
+    ("异世界行记", "OtherworldlyStory"),
+    ("翡玉法球", "EmeraldOrb"),
+    ("甲级宝珏", "TwinNephrite"),
+    ("西风秘典", "FavoniusCodex"),
+    ("流浪乐章", "TheWidsith"),
+    ("祭礼残章", "SacrificialFragments"),
+    ("宗室秘法录", "RoyalGrimoire"),
+    ("匣里日月", "SolarPearl"),
+    ("试作金珀", "PrototypeAmber"),
+    ("万国诸海图谱", "MappaMare"),
+    ("黑岩绯玉", "BlackcliffAgate"),
+    ("昭心", "EyeOfPerception"),
+    ("暗巷的酒与诗", "WineAndSong"),
+    ("忍冬之果", "Frostbearer"),
+    ("嘟嘟可故事集", "DodocoTales"),
+    ("白辰之环", "HakushinRing"),
+    ("证誓之明瞳", "OathswornEye"),
+    ("四风原典", "LostPrayerToTheSacredWinds"),
+    ("天空之卷", "SkywardAtlas"),
+    ("尘世之锁", "MemoryOfDust"),
+    ("不灭月华", "EverlastingMoonglow"),
+    // bow
+    ("猎弓", "HuntersBow"),
+    ("历练的猎弓", "SeasonedHuntersBow"),
+    ("鸦羽弓", "RavenBow"),
+    ("神射手之誓", "SharpshootersOath"),
+    ("反曲弓", "RecurveBow"),
+    ("弹弓", "Slingshot"),
+    ("信使", "Messenger"),
+    ("西风猎弓", "FavoniusWarbow"),
+    ("绝弦", "TheStringless"),
+    ("祭礼弓", "SacrificialBow"),
+    ("宗室长弓", "RoyalBow"),
+    ("弓藏", "Rust"),
+    ("试作澹月", "PrototypeCrescent"),
+    ("钢轮弓", "CompoundBow"),
+    ("黑岩战弓", "BlackcliffWarbow"),
+    ("苍翠猎弓", "TheViridescentHunt"),
+    ("暗巷猎手", "AlleyHunter"),
+    ("幽夜华尔兹", "MitternachtsWaltz"),
+    ("风花之颂", "WindblumeOde"),
+    ("破魔之弓", "Hamayumi"),
+    ("掠食者", "Predator"),
+    ("曚云之月", "MouunsMoon"),
+    ("天空之翼", "SkywardHarp"),
+    ("阿莫斯之弓", "AmosBow"),
+    ("终末嗟叹之诗", "ElegyForTheEnd"),
+    ("飞雷之弦振", "ThunderingPulse"),
+    ("冬极白星", "PolarStar"),
+];
+
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+pub enum WeaponName {
+    // key in WEAPONS, the same as the GOOD weapon key
+    Known(&'static str),
+    // see UnknownNames in the scanner
+    Unknown { raw_title: String },
+}
+
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+pub struct InternalWeapon {
+    pub name: WeaponName,
+    pub star: u32,
+    pub level: u32,
+    pub ascension: u32,
+    pub refinement: u32,
+    pub equip: Option<String>,
+}
+
+impl WeaponName {
+    pub fn from_zh_cn(s: &str) -> Option<WeaponName> {
+        // some names are shown in quotes, e.g. "「渔获」"
+        let s = s.trim_matches(|c| c == '「' || c == '」');
+        WEAPONS.iter().find(|(zh, _)| *zh == s).map(|(_, key)| WeaponName::Known(key))
+    }
+
+    pub fn from_key(key: &str) -> Option<WeaponName> {
+        WEAPONS.iter().find(|(_, k)| *k == key).map(|(_, key)| WeaponName::Known(key))
+    }
+
+    // "Unknown" for weapons not in the catalog
+    pub fn key(&self) -> &'static str {
+        match self {
+            WeaponName::Known(key) => key,
+            WeaponName::Unknown { .. } => "Unknown",
+        }
+    }
+}
+
+impl InternalWeapon {
+    pub fn max_level(&self) -> u32 {
+        level::max_level(self.ascension)
+    }
+}
+
+impl fmt::Display for InternalWeapon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            WeaponName::Known(key) => write!(f, "{}", key)?,
+            WeaponName::Unknown { ref raw_title } => write!(f, "Unknown({})", raw_title)?,
+        }
+        write!(f, " {}* {}/{} R{}", self.star, self.level, self.max_level(), self.refinement)
+    }
+}